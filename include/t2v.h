@@ -0,0 +1,59 @@
+/*
+ * T2V - Text to Voice Reader, C interface
+ *
+ * Link against the `text_to_voice` cdylib built by `cargo build --release`
+ * (libtext_to_voice.so, libtext_to_voice.dylib or text_to_voice.dll).
+ *
+ * Functions returning int use the T2V_* status codes below. After a failure,
+ * t2v_last_error() returns a message describing it; the pointer stays valid
+ * until the next failing call on the same thread.
+ */
+
+#ifndef T2V_H
+#define T2V_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define T2V_OK 0
+#define T2V_ERR_FILE 1
+#define T2V_ERR_SPEECH 2
+#define T2V_ERR_VOICE_NOT_FOUND 3
+#define T2V_ERR_CONFIG 4
+#define T2V_ERR_SYSTEM 5
+#define T2V_ERR_INVALID_ARGUMENT (-1)
+#define T2V_ERR_PANIC (-2)
+
+typedef struct T2vEngine T2vEngine;
+
+/* Create an engine for the current platform. voice may be NULL and rate may
+ * be 0 to use the defaults. Returns NULL on failure. */
+T2vEngine *t2v_engine_new(const char *voice, uint32_t rate);
+
+/* Release an engine created by t2v_engine_new. Accepts NULL. */
+void t2v_engine_free(T2vEngine *engine);
+
+/* Speak UTF-8 text through the system audio output. */
+int t2v_speak(const T2vEngine *engine, const char *text);
+
+/* Synthesize UTF-8 text into a WAV file at path. */
+int t2v_render_wav(const T2vEngine *engine, const char *text, const char *path);
+
+/* Installed voices as a JSON array of {"name", "language", "description"}
+ * objects. Returns NULL on failure; release the result with t2v_string_free. */
+char *t2v_list_voices_json(const T2vEngine *engine);
+
+/* Release a string returned by this library. Accepts NULL. */
+void t2v_string_free(char *value);
+
+/* Message for the most recent failure on this thread, or NULL. */
+const char *t2v_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* T2V_H */
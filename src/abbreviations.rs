@@ -0,0 +1,482 @@
+//! Abbreviations and acronyms read the way people say them: "approx." as
+//! "approximately", "API" letter by letter and "NASA" as a word
+//!
+//! Each language has a built-in dictionary, and users add their own entries.
+//! An entry says how to read its text: `spell` reads the letters one by one,
+//! `word` reads it as a word, and anything else is the expansion to say.
+
+use crate::text::{Locale, TextFilter, outside_markup};
+use std::collections::HashMap;
+
+/// How an abbreviation or acronym is read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reading {
+    /// Letter by letter, as in "A P I"
+    Spell,
+    /// As a word, as in "NASA"
+    Word,
+    /// Replaced by these words, as in "approximately"
+    Say(String),
+}
+
+impl Reading {
+    /// Parse the value of a dictionary entry: `spell`, `word` or an expansion
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "spell" => Reading::Spell,
+            "word" => Reading::Word,
+            expansion => Reading::Say(expansion.to_string()),
+        }
+    }
+}
+
+const ENGLISH: &[(&str, &str)] = &[
+    ("approx.", "approximately"),
+    ("e.g.", "for example"),
+    ("i.e.", "that is"),
+    ("etc.", "et cetera"),
+    ("et al.", "and others"),
+    ("cf.", "compare"),
+    ("vs.", "versus"),
+    ("ca.", "circa"),
+    ("esp.", "especially"),
+    ("incl.", "including"),
+    ("excl.", "excluding"),
+    ("misc.", "miscellaneous"),
+    ("avg.", "average"),
+    ("dept.", "department"),
+    ("Fig.", "Figure"),
+    ("Figs.", "Figures"),
+    ("Eq.", "Equation"),
+    ("Sec.", "Section"),
+    ("Ch.", "Chapter"),
+    ("Vol.", "Volume"),
+    ("pp.", "pages"),
+    ("Mr.", "Mister"),
+    ("Mrs.", "Missus"),
+    ("Dr.", "Doctor"),
+    ("Prof.", "Professor"),
+    ("Jr.", "Junior"),
+    ("Sr.", "Senior"),
+    ("Inc.", "Incorporated"),
+    ("Ltd.", "Limited"),
+    ("Corp.", "Corporation"),
+    ("w/o", "without"),
+    ("w/", "with"),
+    ("a.m.", "A M"),
+    ("p.m.", "P M"),
+    ("U.S.", "U S"),
+    ("AI", "spell"),
+    ("API", "spell"),
+    ("AWS", "spell"),
+    ("BBC", "spell"),
+    ("CEO", "spell"),
+    ("CI", "spell"),
+    ("CLI", "spell"),
+    ("CPU", "spell"),
+    ("CSS", "spell"),
+    ("CSV", "spell"),
+    ("CTO", "spell"),
+    ("DNS", "spell"),
+    ("EU", "spell"),
+    ("FAQ", "spell"),
+    ("FBI", "spell"),
+    ("GPU", "spell"),
+    ("HR", "spell"),
+    ("HTML", "spell"),
+    ("HTTP", "spell"),
+    ("HTTPS", "spell"),
+    ("IBM", "spell"),
+    ("ID", "spell"),
+    ("IDE", "spell"),
+    ("IP", "spell"),
+    ("JSON", "Jason"),
+    ("ML", "spell"),
+    ("OS", "spell"),
+    ("PC", "spell"),
+    ("PDF", "spell"),
+    ("PR", "spell"),
+    ("QA", "spell"),
+    ("SDK", "spell"),
+    ("SQL", "spell"),
+    ("SSD", "spell"),
+    ("SSH", "spell"),
+    ("TCP", "spell"),
+    ("TV", "spell"),
+    ("UDP", "spell"),
+    ("UI", "spell"),
+    ("UK", "spell"),
+    ("URL", "spell"),
+    ("USA", "spell"),
+    ("USB", "spell"),
+    ("UX", "spell"),
+    ("VM", "spell"),
+    ("XML", "spell"),
+    ("ASCII", "word"),
+    ("FIFA", "word"),
+    ("GIF", "word"),
+    ("LAN", "word"),
+    ("NASA", "word"),
+    ("NATO", "word"),
+    ("PIN", "word"),
+    ("RAID", "word"),
+    ("RAM", "word"),
+    ("SIM", "word"),
+    ("UNESCO", "word"),
+    ("UNICEF", "word"),
+    ("WAN", "word"),
+    ("GUI", "gooey"),
+    ("JPEG", "jay peg"),
+    ("SCSI", "scuzzy"),
+];
+
+const GERMAN: &[(&str, &str)] = &[
+    ("z. B.", "zum Beispiel"),
+    ("d. h.", "das heißt"),
+    ("u. a.", "unter anderem"),
+    ("z. T.", "zum Teil"),
+    ("o. Ä.", "oder Ähnliches"),
+    ("usw.", "und so weiter"),
+    ("bzw.", "beziehungsweise"),
+    ("bspw.", "beispielsweise"),
+    ("ca.", "circa"),
+    ("etc.", "et cetera"),
+    ("evtl.", "eventuell"),
+    ("ggf.", "gegebenenfalls"),
+    ("inkl.", "inklusive"),
+    ("exkl.", "exklusive"),
+    ("sog.", "sogenannte"),
+    ("vgl.", "vergleiche"),
+    ("Nr.", "Nummer"),
+    ("Abb.", "Abbildung"),
+    ("Tab.", "Tabelle"),
+    ("Kap.", "Kapitel"),
+    ("Str.", "Straße"),
+    ("Hr.", "Herr"),
+    ("Dr.", "Doktor"),
+    ("Prof.", "Professor"),
+    ("AG", "spell"),
+    ("API", "spell"),
+    ("CPU", "spell"),
+    ("DB", "spell"),
+    ("EU", "spell"),
+    ("GmbH", "spell"),
+    ("HTML", "spell"),
+    ("KI", "spell"),
+    ("LKW", "spell"),
+    ("Lkw", "spell"),
+    ("PDF", "spell"),
+    ("PKW", "spell"),
+    ("Pkw", "spell"),
+    ("SQL", "spell"),
+    ("URL", "spell"),
+    ("USA", "spell"),
+    ("USB", "spell"),
+    ("ADAC", "spell"),
+    ("NASA", "word"),
+    ("NATO", "word"),
+    ("PIN", "word"),
+    ("RAM", "word"),
+    ("UNESCO", "word"),
+];
+
+/// Abbreviations that lead into the word after them, as in "Dr. Smith" or
+/// "z. B. Häuser", so a capital after them does not start a sentence
+const LEADING: &[&str] = &[
+    "approx.", "e.g.", "i.e.", "cf.", "vs.", "ca.", "esp.", "incl.", "excl.", "Fig.", "Figs.",
+    "Eq.", "Sec.", "Ch.", "Vol.", "pp.", "Mr.", "Mrs.", "Dr.", "Prof.", "z. B.", "d. h.", "u. a.",
+    "z. T.", "bzw.", "bspw.", "evtl.", "ggf.", "inkl.", "exkl.", "sog.", "vgl.", "Nr.", "Abb.",
+    "Tab.", "Kap.", "Hr.",
+];
+
+/// Read abbreviations and acronyms from a dictionary, such as "etc." as
+/// "et cetera" and "API" as "A P I"
+///
+/// Acronyms match whole words with their case, and also with a plural "s".
+/// Abbreviations with punctuation match with or without the spaces inside
+/// them, so "z. B." also finds "z.B.", and a capital first letter carries
+/// over to the expansion.
+#[derive(Debug, Clone, Default)]
+pub struct ExpandAbbreviations {
+    /// Entries that are a single word, such as "API"
+    words: HashMap<String, Reading>,
+    /// Entries with punctuation or spaces, longest first
+    phrases: Vec<(String, Reading)>,
+}
+
+impl ExpandAbbreviations {
+    /// The built-in dictionary for a locale, if there is one, with the
+    /// user's entries added on top
+    pub fn new<I: IntoIterator<Item = (String, Reading)>>(
+        locale: Option<Locale>,
+        entries: I,
+    ) -> Self {
+        let builtin = match locale {
+            Some(Locale::EnUs | Locale::EnGb) => ENGLISH,
+            Some(Locale::De) => GERMAN,
+            None => &[],
+        };
+        let mut filter = ExpandAbbreviations::default();
+        let entries = builtin
+            .iter()
+            .map(|&(text, reading)| (text.to_string(), Reading::parse(reading)))
+            .chain(entries);
+        for (text, reading) in entries {
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            if text.chars().all(char::is_alphanumeric) {
+                filter.words.insert(text, reading);
+            } else {
+                // Later entries replace earlier ones for the same text
+                filter.phrases.retain(|(phrase, _)| *phrase != text);
+                filter.phrases.push((text, reading));
+            }
+        }
+        filter
+            .phrases
+            .sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.len()));
+        filter
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len() + self.phrases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn expand(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut index = 0;
+
+        'scan: while index < text.len() {
+            let at_word_start = text[..index]
+                .chars()
+                .last()
+                .is_none_or(|c| !c.is_alphanumeric());
+            if at_word_start {
+                for (phrase, reading) in &self.phrases {
+                    if let Some(end) = match_phrase(text, index, phrase) {
+                        let matched = &text[index..end];
+                        result.push_str(&read(matched, reading, matched));
+                        // The period of "etc." also ends the sentence it closes
+                        if matched.ends_with('.')
+                            && !LEADING.contains(&phrase.as_str())
+                            && starts_sentence(&text[end..])
+                        {
+                            result.push('.');
+                        }
+                        // "Fig.3" is read "Figure 3", not "Figure3"
+                        if text[end..].starts_with(char::is_alphanumeric) {
+                            result.push(' ');
+                        }
+                        index = end;
+                        continue 'scan;
+                    }
+                }
+                let word_end = text[index..]
+                    .find(|c: char| !c.is_alphanumeric())
+                    .map_or(text.len(), |length| index + length);
+                if word_end > index {
+                    let word = &text[index..word_end];
+                    if let Some(words) = self.word(word) {
+                        result.push_str(&words);
+                    } else {
+                        result.push_str(word);
+                    }
+                    index = word_end;
+                    continue;
+                }
+            }
+            let c = text[index..].chars().next().unwrap_or_default();
+            result.push(c);
+            index += c.len_utf8();
+        }
+        result
+    }
+
+    /// How to read a word, if it is in the dictionary, also as a plural
+    fn word(&self, word: &str) -> Option<String> {
+        if let Some(reading) = self.words.get(word) {
+            return Some(read(word, reading, word));
+        }
+        let singular = word.strip_suffix('s')?;
+        let reading = self.words.get(singular)?;
+        // "APIs" is read "A P I's", so the "s" is not taken for a letter
+        Some(match reading {
+            Reading::Spell => format!("{}'s", read(singular, reading, singular)),
+            _ => format!("{}s", read(singular, reading, singular)),
+        })
+    }
+}
+
+impl TextFilter for ExpandAbbreviations {
+    fn name(&self) -> &str {
+        "abbreviations"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+        outside_markup(text, |text| self.expand(text))
+    }
+}
+
+/// The words for an entry; `matched` is the text as written, whose first
+/// letter decides the case of an expansion
+fn read(entry: &str, reading: &Reading, matched: &str) -> String {
+    match reading {
+        Reading::Spell => entry
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_uppercase)
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" "),
+        // Lowercase, so engines that spell out capitals read it as a word
+        Reading::Word => {
+            let mut chars = entry.chars();
+            chars
+                .next()
+                .map(|first| {
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        Reading::Say(expansion) => {
+            if matched.chars().next().is_some_and(char::is_uppercase) {
+                let mut chars = expansion.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            } else {
+                expansion.clone()
+            }
+        }
+    }
+}
+
+/// Whether a new sentence starts after an abbreviation: the line ends, or
+/// whitespace and a capital letter follow
+fn starts_sentence(rest: &str) -> bool {
+    let next = rest.trim_start();
+    let gap = &rest[..rest.len() - next.len()];
+    next.is_empty()
+        || gap.contains('\n')
+        || (!gap.is_empty() && next.starts_with(char::is_uppercase))
+}
+
+/// Where a phrase entry that starts at `index` ends; spaces in the entry
+/// match any whitespace or none, and a lowercase first letter also matches
+/// a capital, as at the start of a sentence
+fn match_phrase(text: &str, index: usize, phrase: &str) -> Option<usize> {
+    let mut position = index;
+    for (i, expected) in phrase.chars().enumerate() {
+        if expected == ' ' {
+            position += text[position..]
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(text.len() - position);
+            continue;
+        }
+        let c = text[position..].chars().next()?;
+        let same = c == expected
+            || (i == 0 && expected.is_lowercase() && c.to_lowercase().eq(expected.to_lowercase()));
+        if !same {
+            return None;
+        }
+        position += c.len_utf8();
+    }
+    // "w/" must not match the start of "w/o" or "w/e", and "ca." must not
+    // end inside a word
+    let ends_word = phrase
+        .chars()
+        .last()
+        .is_some_and(|c| c.is_alphanumeric() || c == '/');
+    let next = text[position..].chars().next();
+    if ends_word && next.is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    Some(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(locale: Locale, text: &str) -> String {
+        ExpandAbbreviations::new(Some(locale), Vec::new()).apply(text)
+    }
+
+    #[test]
+    fn keeps_the_period_that_ends_a_sentence() {
+        let cases = [
+            (
+                "approx. 5 items, etc. The API by NASA.",
+                "approximately 5 items, et cetera. The A P I by Nasa.",
+            ),
+            (
+                "Made by Acme Inc. It works.",
+                "Made by Acme Incorporated. It works.",
+            ),
+            ("Apples, pears etc.", "Apples, pears et cetera."),
+            (
+                "Apples, pears etc.\nNext line",
+                "Apples, pears et cetera.\nNext line",
+            ),
+            ("Apples etc. and pears", "Apples et cetera and pears"),
+            ("Apples etc., pears", "Apples et cetera, pears"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expand(Locale::EnUs, input), expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn leading_abbreviations_keep_no_period() {
+        let cases = [
+            ("Dr. Smith and Mr. Jones", "Doctor Smith and Mister Jones"),
+            ("See Fig. 3, e.g. Paris", "See Figure 3, for example Paris"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expand(Locale::EnUs, input), expected, "input: {:?}", input);
+        }
+        assert_eq!(
+            expand(Locale::De, "Obst, z. B. Äpfel usw. Dann"),
+            "Obst, zum Beispiel Äpfel und so weiter. Dann"
+        );
+    }
+
+    #[test]
+    fn expansions_stay_apart_from_what_follows() {
+        let cases = [
+            ("See Fig.3 above", "See Figure 3 above"),
+            (
+                "Coffee w/ milk, w/o sugar",
+                "Coffee with milk, without sugar",
+            ),
+            ("Whatever, w/e", "Whatever, w/e"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expand(Locale::EnUs, input), expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn acronyms_and_user_entries() {
+        assert_eq!(
+            expand(Locale::EnUs, "Two APIs on a GPU"),
+            "Two A P I's on a G P U"
+        );
+        let filter =
+            ExpandAbbreviations::new(Some(Locale::EnUs), vec![("CLI".to_string(), Reading::Word)]);
+        assert_eq!(filter.apply("The CLI"), "The Cli");
+    }
+}
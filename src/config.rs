@@ -1,12 +1,123 @@
-use crate::errors::{TtsError, TtsResult};
-use crate::tts::SpeechConfig;
+use crate::queue::SortOrder;
+use clap::parser::ValueSource;
 use clap::{Arg, ArgMatches, Command as ClapCommand};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use text_to_voice::abbreviations::Reading;
+use text_to_voice::encoding;
+use text_to_voice::formats::code::SourceMode;
+use text_to_voice::formats::{CodeBlockMode, InputFormat, NumberRange, ReadOptions};
+use text_to_voice::text::{
+    DEFAULT_FILTERS, ExpandAbbreviations, ExpandNumbers, Lexicon, Locale, Pipeline,
+};
+use text_to_voice::{SpeechConfig, TextToSpeech, TtsError, TtsResult};
+
+/// File name that stands for standard input
+pub const STDIN_PATH: &str = "-";
+
+/// File read when neither `--file` nor piped input is given
+pub const DEFAULT_FILE: &str = "sample.txt";
+
+/// Config file read when `--config` is not given, if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "t2v.toml";
+
+/// Settings read from the config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    /// Rules for numbers and the abbreviation dictionary: en-us, en-gb or
+    /// de; taken from the voice when not set
+    locale: Option<String>,
+    /// Lexicon files to load, relative to the config file
+    lexicon_files: Vec<PathBuf>,
+    filters: FilterSettings,
+    /// Words and the spellings to speak instead, e.g. nginx = "engine x"
+    lexicon: BTreeMap<String, String>,
+    /// Abbreviations and how to read them: "spell", "word" or the words to say
+    abbreviations: BTreeMap<String, String>,
+    numbers: NumberSettings,
+}
+
+/// The `[numbers]` section, which held the locale before abbreviations used it too
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct NumberSettings {
+    locale: Option<String>,
+}
+
+/// The `[filters]` section: which text filters run, in order
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilterSettings {
+    order: Vec<String>,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        FilterSettings {
+            order: DEFAULT_FILTERS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl FileConfig {
+    /// Read the config file given to `--config`, or `t2v.toml` when present
+    fn load(path: Option<&String>) -> TtsResult<(Option<PathBuf>, Self)> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => PathBuf::from(DEFAULT_CONFIG_PATH),
+            None => return Ok((None, FileConfig::default())),
+        };
+        let content = fs::read_to_string(&path).map_err(|e| {
+            TtsError::FileError(format!(
+                "Cannot read config file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let mut config: FileConfig = toml::from_str(&content).map_err(|e| {
+            TtsError::ConfigError(format!("Invalid config file '{}': {}", path.display(), e))
+        })?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for file in &mut config.lexicon_files {
+            *file = directory.join(&*file);
+        }
+        Ok((Some(path), config))
+    }
+}
 
 /// Application configuration
 #[derive(Debug)]
 pub struct AppConfig {
-    pub file_path: String,
+    pub file_paths: Vec<String>,
+    /// The default file, when piped input took its place; read instead if
+    /// standard input turns out to be empty
+    pub stdin_fallback: Option<String>,
+    pub sort_order: SortOrder,
+    pub format: Option<InputFormat>,
+    /// Read every file as source code, even when its extension is not recognized
+    pub force_code: bool,
+    pub read_options: ReadOptions,
+    pub list_chapters: bool,
+    pub announce: bool,
+    pub output_path: Option<String>,
+    pub incremental: bool,
+    pub follow: bool,
     pub speech_config: SpeechConfig,
+    /// The `--config` argument, read by [`AppConfig::load_text_filters`]
+    config_file: Option<String>,
+    /// Config file the settings below came from, if any
+    pub config_path: Option<PathBuf>,
+    /// Lexicon files loaded from the config file and `--lexicon`
+    pub lexicon_paths: Vec<PathBuf>,
+    /// Text filters run on everything before it is spoken
+    pub pipeline: Pipeline,
     pub list_voices: bool,
     pub show_help: bool,
 }
@@ -29,8 +140,137 @@ impl AppConfig {
                     .short('f')
                     .long("file")
                     .value_name("FILE")
-                    .help("Text file to read aloud")
-                    .default_value("sample.txt"),
+                    .help("Text files, directories or glob patterns to read aloud (use - for standard input)")
+                    .num_args(1..)
+                    .action(clap::ArgAction::Append)
+                    .default_value(DEFAULT_FILE),
+            )
+            .arg(
+                Arg::new("chapter")
+                    .long("chapter")
+                    .value_name("N")
+                    .help("Read only chapter N of an EPUB book")
+                    .value_parser(clap::value_parser!(u64).range(1..)),
+            )
+            .arg(
+                Arg::new("list-chapters")
+                    .long("list-chapters")
+                    .help("List the chapters of an EPUB book without reading it")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("pages")
+                    .long("pages")
+                    .value_name("RANGE")
+                    .help("Read only these pages of a PDF (e.g., 5, 3-10 or 3-)"),
+            )
+            .arg(
+                Arg::new("rows")
+                    .long("rows")
+                    .value_name("RANGE")
+                    .help("Read only these data rows of a CSV or TSV table (e.g., 5, 3-10 or 3-)"),
+            )
+            .arg(
+                Arg::new("columns")
+                    .long("columns")
+                    .value_name("COLUMNS")
+                    .value_delimiter(',')
+                    .help("Read only these CSV or TSV columns, by header name or number (e.g., Name,Role or 1,3)"),
+            )
+            .arg(
+                Arg::new("select")
+                    .long("select")
+                    .value_name("PATH")
+                    .help("Read only the values under this key path of a JSON, YAML or TOML file (e.g., server.hosts[0])"),
+            )
+            .arg(
+                Arg::new("latest")
+                    .long("latest")
+                    .value_name("N")
+                    .help("Read only the N newest items of an RSS or Atom feed")
+                    .value_parser(clap::value_parser!(u64).range(1..)),
+            )
+            .arg(
+                Arg::new("heard")
+                    .long("heard")
+                    .value_name("FILE")
+                    .help("Skip feed items listed in FILE, and add the items read to it"),
+            )
+            .arg(
+                Arg::new("ocr-lang")
+                    .long("ocr-lang")
+                    .value_name("LANGS")
+                    .help("Tesseract language(s) for reading images and scanned PDFs (e.g., eng, deu or eng+fra)"),
+            )
+            .arg(
+                Arg::new("skip-todo")
+                    .long("skip-todo")
+                    .help("Leave out Org-mode TODO keywords, priorities, tags and planning lines")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("read-drawers")
+                    .long("read-drawers")
+                    .help("Read Org-mode drawers such as :PROPERTIES: (left out by default)")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("code")
+                    .long("code")
+                    .value_name("MODE")
+                    .num_args(0..=1)
+                    .default_missing_value("comments")
+                    .help("Read files as source code: comments (only comments and docstrings, the default) or full"),
+            )
+            .arg(
+                Arg::new("read-outputs")
+                    .long("read-outputs")
+                    .help("Also read the text outputs of Jupyter notebook code cells")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("sort")
+                    .long("sort")
+                    .value_name("ORDER")
+                    .help("Order for files found in directories or glob patterns: name or mtime")
+                    .default_value("name"),
+            )
+            .arg(
+                Arg::new("encoding")
+                    .long("encoding")
+                    .value_name("ENCODING")
+                    .help("Character encoding of the input (e.g., utf-8, windows-1252, utf-16le); detected when omitted"),
+            )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .help("Input format: text, markdown, html, epub, pdf, docx, odt, image, subtitles, email, notebook, latex, csv, json, yaml, toml, feed, org, rst or code (detected from the file extension when omitted)"),
+            )
+            .arg(
+                Arg::new("code-blocks")
+                    .long("code-blocks")
+                    .value_name("MODE")
+                    .help("How to read code blocks in documents: skip, summarize or read")
+                    .default_value("summarize"),
+            )
+            .arg(
+                Arg::new("announce")
+                    .long("announce")
+                    .help("Speak each file name before reading the file")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("incremental")
+                    .long("incremental")
+                    .help("Speak each line of standard input as it arrives")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("follow")
+                    .long("follow")
+                    .help("Keep watching the file and speak lines appended to it (like tail -f)")
+                    .action(clap::ArgAction::SetTrue),
             )
             .arg(
                 Arg::new("voice")
@@ -47,6 +287,26 @@ impl AppConfig {
                     .help("Speaking rate (words per minute, e.g., 200)")
                     .value_parser(clap::value_parser!(u32)),
             )
+            .arg(
+                Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .value_name("WAV")
+                    .help("Write the speech to a WAV file instead of playing it"),
+            )
+            .arg(
+                Arg::new("config")
+                    .long("config")
+                    .value_name("FILE")
+                    .help("Read settings such as text filters and the lexicon from FILE (default: t2v.toml if present)"),
+            )
+            .arg(
+                Arg::new("lexicon")
+                    .long("lexicon")
+                    .value_name("FILE")
+                    .help("Pronunciation lexicon (TOML or CSV) to use on top of the config file; can be repeated")
+                    .action(clap::ArgAction::Append),
+            )
             .arg(
                 Arg::new("list-voices")
                     .short('l')
@@ -64,40 +324,185 @@ impl AppConfig {
 
     /// Parse command-line arguments into configuration
     fn parse_matches(matches: &ArgMatches) -> TtsResult<Self> {
-        let file_path = matches.get_one::<String>("file").unwrap().clone();
+        let mut file_paths: Vec<String> = matches
+            .get_many::<String>("file")
+            .unwrap()
+            .cloned()
+            .collect();
+        let sort_order = SortOrder::parse(matches.get_one::<String>("sort").unwrap())?;
+        let encoding = matches
+            .get_one::<String>("encoding")
+            .map(|label| encoding::encoding_for_label(label))
+            .transpose()?;
+        let format = matches
+            .get_one::<String>("format")
+            .map(|name| InputFormat::from_name(name))
+            .transpose()?;
+        let read_options = ReadOptions {
+            encoding,
+            chapter: matches
+                .get_one::<u64>("chapter")
+                .map(|&chapter| chapter as usize),
+            pages: matches
+                .get_one::<String>("pages")
+                .map(|range| NumberRange::parse(range))
+                .transpose()?,
+            rows: matches
+                .get_one::<String>("rows")
+                .map(|range| NumberRange::parse(range))
+                .transpose()?,
+            columns: matches
+                .get_many::<String>("columns")
+                .map(|columns| columns.cloned().collect())
+                .unwrap_or_default(),
+            select: matches.get_one::<String>("select").cloned(),
+            latest: matches
+                .get_one::<u64>("latest")
+                .map(|&latest| latest as usize),
+            heard: matches.get_one::<String>("heard").map(PathBuf::from),
+            code_blocks: CodeBlockMode::parse(matches.get_one::<String>("code-blocks").unwrap())?,
+            ocr_language: matches.get_one::<String>("ocr-lang").cloned(),
+            read_outputs: matches.get_flag("read-outputs"),
+            skip_todo: matches.get_flag("skip-todo"),
+            read_drawers: matches.get_flag("read-drawers"),
+            source_mode: matches
+                .get_one::<String>("code")
+                .map(|mode| SourceMode::parse(mode))
+                .transpose()?
+                .unwrap_or_default(),
+        };
+        let output_path = matches.get_one::<String>("output").cloned();
+        let incremental = matches.get_flag("incremental");
+        let follow = matches.get_flag("follow");
 
-        let voice = matches.get_one::<String>("voice").cloned();
-        let rate = matches.get_one::<u32>("rate").copied();
+        // Piped input replaces the default file when no --file was given
+        let mut stdin_fallback = None;
+        if matches.value_source("file") == Some(ValueSource::DefaultValue)
+            && !io::stdin().is_terminal()
+        {
+            stdin_fallback = file_paths.pop();
+            file_paths = vec![STDIN_PATH.to_string()];
+        }
 
-        // Validate rate if provided
-        if let Some(rate) = rate {
-            Self::validate_rate(rate)?;
+        if incremental && file_paths != [STDIN_PATH] {
+            return Err(TtsError::ConfigError(
+                "--incremental only applies when reading standard input".to_string(),
+            ));
+        }
+        if incremental && output_path.is_some() {
+            return Err(TtsError::ConfigError(
+                "--incremental cannot be combined with --output".to_string(),
+            ));
+        }
+        if follow && output_path.is_some() {
+            return Err(TtsError::ConfigError(
+                "--follow cannot be combined with --output".to_string(),
+            ));
         }
 
+        let voice = matches.get_one::<String>("voice").cloned();
+        let rate = matches.get_one::<u32>("rate").copied();
+
         let speech_config = SpeechConfig { voice, rate };
+        speech_config.validate()?;
 
         Ok(AppConfig {
-            file_path,
+            file_paths,
+            stdin_fallback,
+            sort_order,
+            format,
+            force_code: matches.get_one::<String>("code").is_some(),
+            read_options,
+            list_chapters: matches.get_flag("list-chapters"),
+            announce: matches.get_flag("announce"),
+            output_path,
+            incremental,
+            follow,
             speech_config,
+            config_file: matches.get_one::<String>("config").cloned(),
+            config_path: None,
+            lexicon_paths: matches
+                .get_many::<String>("lexicon")
+                .into_iter()
+                .flatten()
+                .map(PathBuf::from)
+                .collect(),
+            pipeline: Pipeline::new(),
             list_voices: matches.get_flag("list-voices"),
             show_help: matches.get_flag("bighelp"),
         })
     }
 
-    /// Validate that the speech rate is within reasonable bounds
-    fn validate_rate(rate: u32) -> TtsResult<()> {
-
-        // valid range is 50-1000 wpm
- 
-        //clippy likes this line better, which does not work properly with all compilers.
-        // if !(500..=1000).contains(&rate) { 
-
-        if rate < 50 || rate > 1000 { 
-            return Err(TtsError::ConfigError(format!(
-                "Speech rate {} is outside valid range (50-1000 words per minute)",
-                rate
-            )));
+    /// Read the config file and lexicons and set up the text filters
+    ///
+    /// Only reading text needs them, so a broken config file does not get in
+    /// the way of `--list-voices` or `--bighelp`.
+    ///
+    /// The voice language is only looked up, which lists the engine's
+    /// voices, when a lexicon entry or the numbers and abbreviations rules
+    /// depend on it.
+    pub fn load_text_filters(&mut self, engine: &dyn TextToSpeech) -> TtsResult<()> {
+        let (config_path, file_config) = FileConfig::load(self.config_file.as_ref())?;
+        let mut lexicon = Lexicon::new(file_config.lexicon);
+        // Lexicons from the config file come before those given to --lexicon
+        let mut lexicon_paths = file_config.lexicon_files;
+        lexicon_paths.append(&mut self.lexicon_paths);
+        for path in &lexicon_paths {
+            lexicon.extend(Lexicon::load(path)?);
         }
+        let locale = file_config
+            .locale
+            .or(file_config.numbers.locale)
+            .as_deref()
+            .map(Locale::parse)
+            .transpose()?;
+        let order = &file_config.filters.order;
+        let needs_locale = locale.is_none()
+            && order
+                .iter()
+                .any(|name| name == "numbers" || name == "abbreviations");
+        let language = (lexicon.has_language_entries() || needs_locale)
+            .then(|| voice_language(engine, self.speech_config.voice.as_deref()));
+        let lexicon =
+            lexicon.for_engine(engine.name(), engine.phoneme_markup(), language.as_deref());
+        // Numbers and abbreviations in a language without rules are left to the engine
+        let locale = locale.or_else(|| language.as_deref().and_then(Locale::from_language));
+        let abbreviations = ExpandAbbreviations::new(
+            locale,
+            file_config
+                .abbreviations
+                .into_iter()
+                .map(|(text, reading)| (text, Reading::parse(&reading))),
+        );
+        self.pipeline = Pipeline::from_names(
+            order,
+            vec![
+                Box::new(lexicon),
+                Box::new(abbreviations),
+                Box::new(ExpandNumbers::new(locale)),
+            ],
+        )?;
+
+        self.config_path = config_path;
+        self.lexicon_paths = lexicon_paths;
         Ok(())
     }
 }
+
+/// Language of the chosen voice as the engine reports it, for per-language
+/// lexicon entries and the locale; the default voice is taken to be English
+fn voice_language(engine: &dyn TextToSpeech, voice: Option<&str>) -> String {
+    let Some(voice) = voice else {
+        return "en".to_string();
+    };
+    // espeak voices are usually chosen by language, e.g. -v de
+    engine
+        .voices()
+        .ok()
+        .and_then(|voices| {
+            voices.into_iter().find(|v| {
+                v.name.eq_ignore_ascii_case(voice) || v.language.eq_ignore_ascii_case(voice)
+            })
+        })
+        .map_or_else(|| voice.to_string(), |v| v.language)
+}
@@ -0,0 +1,232 @@
+//! Timed re-voicing of subtitles into a single WAV track
+//!
+//! Each cue is rendered on its own and placed at its start time. A cue may
+//! speak until the next cue starts; clips longer than that are rendered again
+//! at a faster rate until they fit or the rate limit is reached.
+
+use crate::errors::{TtsError, TtsResult};
+use crate::formats::subtitles::Cue;
+use crate::temp::TempDir;
+use crate::tts::{SpeechConfig, TextToSpeech};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::Duration;
+
+/// Rate used when the configuration leaves it to the engine
+const DEFAULT_RATE: u32 = 200;
+
+/// Fastest rate a clip is sped up to, matching `validate_rate`
+const MAX_RATE: u32 = 1000;
+
+/// Re-render attempts per clip; engines round rates, so one may not be enough
+const SPEED_UP_ATTEMPTS: usize = 3;
+
+/// What happened while rendering a subtitle track
+#[derive(Debug, Clone, Default)]
+pub struct DubReport {
+    pub cues: usize,
+    /// Cues rendered again at a faster rate to fit their slot
+    pub sped_up: usize,
+    /// Cues still too long at the fastest rate; they overlap the next cue
+    pub overrunning: usize,
+    /// Fastest rate any cue was rendered at
+    pub fastest_rate: u32,
+    pub duration: Duration,
+}
+
+/// A rendered clip as 16-bit samples
+struct Clip {
+    spec: WavSpec,
+    samples: Vec<i16>,
+}
+
+impl Clip {
+    fn duration(&self) -> Duration {
+        let frames = self.samples.len() as f64 / self.spec.channels as f64;
+        Duration::from_secs_f64(frames / self.spec.sample_rate as f64)
+    }
+}
+
+/// Render `cues` into one WAV file with each cue starting at its timestamp
+///
+/// Cues left without text are not rendered; the track still lasts until the
+/// latest cue ends.
+pub fn render_cues(
+    engine: &dyn TextToSpeech,
+    cues: &[Cue],
+    config: &SpeechConfig,
+    output: &Path,
+) -> TtsResult<DubReport> {
+    let workspace = TempDir::create("dub")?;
+    let base_rate = config.rate.unwrap_or(DEFAULT_RATE);
+
+    let mut spoken: Vec<&Cue> = cues
+        .iter()
+        .filter(|cue| !cue.text.trim().is_empty())
+        .collect();
+    spoken.sort_by_key(|cue| cue.start);
+    let mut report = DubReport {
+        cues: spoken.len(),
+        fastest_rate: base_rate,
+        ..DubReport::default()
+    };
+
+    let mut mixer: Option<Mixer> = None;
+    for (index, cue) in spoken.iter().enumerate() {
+        // Overlapping cues only get their own duration
+        let slot_end = spoken
+            .get(index + 1)
+            .map(|next| next.start)
+            .filter(|&next_start| next_start > cue.start)
+            .unwrap_or(cue.end)
+            .max(cue.end);
+        let slot = slot_end - cue.start;
+
+        let clip_path = workspace.path.join(format!("cue-{}.wav", index + 1));
+        let mut rate = base_rate;
+        let mut clip = render_clip(engine, &cue.text, config, rate, &clip_path)?;
+
+        let mut attempts = 0;
+        while clip.duration() > slot && rate < MAX_RATE && attempts < SPEED_UP_ATTEMPTS {
+            // Aim slightly under the slot so rounding in the engine still fits
+            let ratio = clip.duration().as_secs_f64() / slot.as_secs_f64().max(0.1);
+            rate = ((rate as f64 * ratio * 1.05).ceil() as u32).clamp(rate + 1, MAX_RATE);
+            clip = render_clip(engine, &cue.text, config, rate, &clip_path)?;
+            attempts += 1;
+        }
+        if rate != base_rate {
+            report.sped_up += 1;
+            report.fastest_rate = report.fastest_rate.max(rate);
+        }
+        if clip.duration() > slot {
+            report.overrunning += 1;
+        }
+
+        let mixer = match &mut mixer {
+            Some(mixer) => mixer,
+            None => mixer.insert(Mixer::create(output, clip.spec)?),
+        };
+        mixer.add(cue.start, clip)?;
+    }
+
+    let Some(mixer) = mixer else {
+        return Err(TtsError::ConfigError(
+            "No subtitle cues to render".to_string(),
+        ));
+    };
+    let track_end = cues.iter().map(|cue| cue.end).max().unwrap_or_default();
+    report.duration = mixer.finish(track_end)?;
+
+    Ok(report)
+}
+
+/// Render one cue at the given rate and load its samples
+fn render_clip(
+    engine: &dyn TextToSpeech,
+    text: &str,
+    config: &SpeechConfig,
+    rate: u32,
+    path: &Path,
+) -> TtsResult<Clip> {
+    let config = SpeechConfig {
+        rate: Some(rate),
+        ..config.clone()
+    };
+    engine.render_wav(text, &config, path)?;
+
+    let reader = WavReader::open(path).map_err(wav_error)?;
+    let spec = reader.spec();
+    if spec.sample_format != SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(TtsError::SpeechError(format!(
+            "Expected 16-bit audio from the speech engine, got {}-bit {:?}",
+            spec.bits_per_sample, spec.sample_format
+        )));
+    }
+    let samples = reader
+        .into_samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(wav_error)?;
+    Ok(Clip { spec, samples })
+}
+
+/// Sums clips into the output file at their start times
+///
+/// Clips must be added in order of their start time. Samples before the
+/// latest start are written out, so only clips still sounding are kept.
+struct Mixer {
+    spec: WavSpec,
+    writer: WavWriter<BufWriter<File>>,
+    /// Samples written so far, counting every channel
+    written: usize,
+    /// Clips still sounding, with the sample they start at
+    active: Vec<(usize, Clip)>,
+}
+
+impl Mixer {
+    fn create(output: &Path, spec: WavSpec) -> TtsResult<Self> {
+        Ok(Mixer {
+            spec,
+            writer: WavWriter::create(output, spec).map_err(wav_error)?,
+            written: 0,
+            active: Vec::new(),
+        })
+    }
+
+    fn offset(&self, time: Duration) -> usize {
+        (time.as_secs_f64() * self.spec.sample_rate as f64).round() as usize
+            * self.spec.channels as usize
+    }
+
+    fn add(&mut self, start: Duration, clip: Clip) -> TtsResult<()> {
+        if clip.spec != self.spec {
+            return Err(TtsError::SpeechError(
+                "The speech engine rendered cues in different audio formats".to_string(),
+            ));
+        }
+        let begin = self.offset(start).max(self.written);
+        self.write_until(begin)?;
+        self.active.push((begin, clip));
+        Ok(())
+    }
+
+    /// Write the mixed track up to sample `end`, dropping clips that have finished
+    fn write_until(&mut self, end: usize) -> TtsResult<()> {
+        while self.written < end {
+            let position = self.written;
+            let sum: i32 = self
+                .active
+                .iter()
+                .filter_map(|(begin, clip)| clip.samples.get(position - begin))
+                .map(|&sample| sample as i32)
+                .sum();
+            let sample = sum.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+            self.writer.write_sample(sample).map_err(wav_error)?;
+            self.written += 1;
+        }
+        let written = self.written;
+        self.active
+            .retain(|(begin, clip)| begin + clip.samples.len() > written);
+        Ok(())
+    }
+
+    /// Write the rest of the track, lasting until `minimum` or the end of the
+    /// last clip, whichever is later, and return its duration
+    fn finish(mut self, minimum: Duration) -> TtsResult<Duration> {
+        let end = self
+            .active
+            .iter()
+            .map(|(begin, clip)| begin + clip.samples.len())
+            .fold(self.offset(minimum), usize::max);
+        self.write_until(end)?;
+        self.writer.finalize().map_err(wav_error)?;
+        Ok(Duration::from_secs_f64(
+            end as f64 / self.spec.channels as f64 / self.spec.sample_rate as f64,
+        ))
+    }
+}
+
+fn wav_error(error: hound::Error) -> TtsError {
+    TtsError::SpeechError(format!("WAV audio error: {}", error))
+}
@@ -0,0 +1,124 @@
+//! Character encoding detection for input files
+//!
+//! Files exported from Windows tools are often Windows-1252, Latin-1 or
+//! UTF-16 rather than UTF-8. Decoding looks for a byte order mark first, then
+//! for BOM-less UTF-16, accepts valid UTF-8, and finally guesses the legacy
+//! encoding.
+
+use crate::errors::{TtsError, TtsResult};
+use chardetng::EncodingDetector;
+use std::fmt;
+
+pub use encoding_rs::Encoding;
+use encoding_rs::{UTF_8, UTF_16BE, UTF_16LE};
+
+/// How the encoding of a decoded text was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingSource {
+    /// Set explicitly with `--encoding`
+    Override,
+    /// Found a byte order mark
+    ByteOrderMark,
+    /// The bytes were valid UTF-8
+    Utf8,
+    /// Guessed from the byte patterns
+    Detected,
+}
+
+impl fmt::Display for EncodingSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingSource::Override => write!(f, "set with --encoding"),
+            EncodingSource::ByteOrderMark => write!(f, "byte order mark"),
+            EncodingSource::Utf8 => write!(f, "valid UTF-8"),
+            EncodingSource::Detected => write!(f, "detected"),
+        }
+    }
+}
+
+/// Text decoded from raw file bytes
+#[derive(Debug, Clone)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    pub source: EncodingSource,
+    /// Whether some bytes were invalid and replaced with U+FFFD
+    pub had_errors: bool,
+}
+
+/// Look up an encoding by a label such as `utf-8`, `latin1`, `windows-1252` or `utf-16le`
+pub fn encoding_for_label(label: &str) -> TtsResult<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+        TtsError::ConfigError(format!(
+            "Unknown encoding '{}' (try utf-8, windows-1252, latin1, utf-16le or utf-16be)",
+            label
+        ))
+    })
+}
+
+/// Decode raw bytes, using `forced` when given and detecting the encoding otherwise
+pub fn decode(bytes: &[u8], forced: Option<&'static Encoding>) -> DecodedText {
+    if let Some(encoding) = forced {
+        return decode_with(bytes, encoding, EncodingSource::Override);
+    }
+
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return DecodedText {
+            text: text.into_owned(),
+            encoding,
+            source: EncodingSource::ByteOrderMark,
+            had_errors,
+        };
+    }
+
+    // NUL-heavy UTF-16 is technically valid UTF-8, so check for it first
+    if let Some(encoding) = guess_utf16(bytes) {
+        return decode_with(bytes, encoding, EncodingSource::Detected);
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return DecodedText {
+            text: text.to_string(),
+            encoding: UTF_8,
+            source: EncodingSource::Utf8,
+            had_errors: false,
+        };
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, true);
+    decode_with(bytes, encoding, EncodingSource::Detected)
+}
+
+/// Decode with a known encoding, dropping a matching byte order mark
+fn decode_with(bytes: &[u8], encoding: &'static Encoding, source: EncodingSource) -> DecodedText {
+    let (text, had_errors) = encoding.decode_with_bom_removal(bytes);
+    DecodedText {
+        text: text.into_owned(),
+        encoding,
+        source,
+        had_errors,
+    }
+}
+
+/// Recognize BOM-less UTF-16 from the zero bytes that ASCII characters leave behind
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 4 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let pairs = bytes.len() / 2;
+    let even_zeros = bytes.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_zeros = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+
+    // Mostly-ASCII text has a zero in the high byte of nearly every code unit
+    if odd_zeros * 10 >= pairs * 7 && even_zeros * 10 <= pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 7 && odd_zeros * 10 <= pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,268 @@
+//! C ABI for embedding T2V in non-Rust programs
+//!
+//! The matching declarations live in `include/t2v.h`. Every function that can
+//! fail returns a `T2V_*` status code; the message for the most recent failure
+//! on the calling thread is available from [`t2v_last_error`].
+
+use crate::errors::TtsError;
+use crate::tts::{SpeechConfig, TextToSpeech, create_tts_engine, validate_rate};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+/// The call succeeded
+pub const T2V_OK: c_int = 0;
+/// [`TtsError::FileError`]
+pub const T2V_ERR_FILE: c_int = 1;
+/// [`TtsError::SpeechError`]
+pub const T2V_ERR_SPEECH: c_int = 2;
+/// [`TtsError::VoiceNotFound`]
+pub const T2V_ERR_VOICE_NOT_FOUND: c_int = 3;
+/// [`TtsError::ConfigError`]
+pub const T2V_ERR_CONFIG: c_int = 4;
+/// [`TtsError::SystemError`]
+pub const T2V_ERR_SYSTEM: c_int = 5;
+/// A NULL pointer or a string that is not valid UTF-8 was passed in
+pub const T2V_ERR_INVALID_ARGUMENT: c_int = -1;
+/// The library panicked; the call was abandoned instead of aborting the host
+pub const T2V_ERR_PANIC: c_int = -2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Opaque engine handle handed out to C callers
+pub struct T2vEngine {
+    engine: Box<dyn TextToSpeech>,
+    config: SpeechConfig,
+}
+
+/// Map an error to its C status code
+fn error_code(error: &TtsError) -> c_int {
+    match error {
+        TtsError::FileError(_) => T2V_ERR_FILE,
+        TtsError::SpeechError(_) => T2V_ERR_SPEECH,
+        TtsError::VoiceNotFound(_) => T2V_ERR_VOICE_NOT_FOUND,
+        TtsError::ConfigError(_) => T2V_ERR_CONFIG,
+        TtsError::SystemError(_) => T2V_ERR_SYSTEM,
+    }
+}
+
+/// Remember an error message for `t2v_last_error`
+fn set_last_error(message: &str) {
+    // Interior NULs cannot cross the C boundary, so drop them from the message
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Record an error and return its status code
+fn fail(error: &TtsError) -> c_int {
+    set_last_error(&error.to_string());
+    error_code(error)
+}
+
+/// Run the body of an exported function, returning `on_panic` if it panics
+///
+/// A panic must not unwind into the C caller, where it would abort the whole
+/// host process, so it is stopped here and reported through `t2v_last_error`.
+fn catch_panic<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let detail = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        set_last_error(&format!("Internal error: {}", detail));
+        on_panic
+    })
+}
+
+/// Borrow a C string argument as UTF-8, recording an error on failure
+///
+/// # Safety
+///
+/// `value` must be NULL or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Option<&'a str> {
+    if value.is_null() {
+        set_last_error(&format!("Argument '{}' must not be NULL", name));
+        return None;
+    }
+    match unsafe { CStr::from_ptr(value) }.to_str() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            set_last_error(&format!("Argument '{}' is not valid UTF-8", name));
+            None
+        }
+    }
+}
+
+/// Create an engine for the current platform
+///
+/// `voice` may be NULL for the system default voice, and `rate` may be 0 for
+/// the default speaking rate. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `voice` must be NULL or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t2v_engine_new(voice: *const c_char, rate: u32) -> *mut T2vEngine {
+    catch_panic(ptr::null_mut(), || {
+        let voice = if voice.is_null() {
+            None
+        } else {
+            match unsafe { str_arg(voice, "voice") } {
+                Some(voice) => Some(voice.to_string()),
+                None => return ptr::null_mut(),
+            }
+        };
+
+        let rate = if rate == 0 { None } else { Some(rate) };
+        if let Some(rate) = rate
+            && let Err(e) = validate_rate(rate)
+        {
+            fail(&e);
+            return ptr::null_mut();
+        }
+
+        let engine = T2vEngine {
+            engine: create_tts_engine(),
+            config: SpeechConfig { voice, rate },
+        };
+        Box::into_raw(Box::new(engine))
+    })
+}
+
+/// Release an engine created by `t2v_engine_new`
+///
+/// # Safety
+///
+/// `engine` must be NULL or a pointer returned by `t2v_engine_new` that has
+/// not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t2v_engine_free(engine: *mut T2vEngine) {
+    catch_panic((), || {
+        if !engine.is_null() {
+            drop(unsafe { Box::from_raw(engine) });
+        }
+    })
+}
+
+/// Speak text through the system audio output
+///
+/// # Safety
+///
+/// `engine` must come from `t2v_engine_new` and `text` must point to a
+/// NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t2v_speak(engine: *const T2vEngine, text: *const c_char) -> c_int {
+    catch_panic(T2V_ERR_PANIC, || {
+        let Some(engine) = (unsafe { engine.as_ref() }) else {
+            set_last_error("Argument 'engine' must not be NULL");
+            return T2V_ERR_INVALID_ARGUMENT;
+        };
+        let Some(text) = (unsafe { str_arg(text, "text") }) else {
+            return T2V_ERR_INVALID_ARGUMENT;
+        };
+
+        match engine.engine.speak(text, &engine.config) {
+            Ok(()) => T2V_OK,
+            Err(e) => fail(&e),
+        }
+    })
+}
+
+/// Synthesize text into a WAV file at `path`
+///
+/// # Safety
+///
+/// `engine` must come from `t2v_engine_new`; `text` and `path` must point to
+/// NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t2v_render_wav(
+    engine: *const T2vEngine,
+    text: *const c_char,
+    path: *const c_char,
+) -> c_int {
+    catch_panic(T2V_ERR_PANIC, || {
+        let Some(engine) = (unsafe { engine.as_ref() }) else {
+            set_last_error("Argument 'engine' must not be NULL");
+            return T2V_ERR_INVALID_ARGUMENT;
+        };
+        let (Some(text), Some(path)) = (unsafe { str_arg(text, "text") }, unsafe {
+            str_arg(path, "path")
+        }) else {
+            return T2V_ERR_INVALID_ARGUMENT;
+        };
+
+        match engine
+            .engine
+            .render_wav(text, &engine.config, Path::new(path))
+        {
+            Ok(()) => T2V_OK,
+            Err(e) => fail(&e),
+        }
+    })
+}
+
+/// List installed voices as a JSON array of `{"name", "language", "description"}`
+///
+/// Returns NULL on failure. The string must be released with `t2v_string_free`.
+///
+/// # Safety
+///
+/// `engine` must come from `t2v_engine_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t2v_list_voices_json(engine: *const T2vEngine) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        let Some(engine) = (unsafe { engine.as_ref() }) else {
+            set_last_error("Argument 'engine' must not be NULL");
+            return ptr::null_mut();
+        };
+
+        let json = engine.engine.voices().and_then(|voices| {
+            serde_json::to_string(&voices).map_err(|e| TtsError::SystemError(e.to_string()))
+        });
+        match json.map(CString::new) {
+            Ok(Ok(json)) => json.into_raw(),
+            Ok(Err(e)) => {
+                set_last_error(&e.to_string());
+                ptr::null_mut()
+            }
+            Err(e) => {
+                fail(&e);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Release a string returned by `t2v_list_voices_json`
+///
+/// # Safety
+///
+/// `value` must be NULL or a pointer returned by this library that has not
+/// been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t2v_string_free(value: *mut c_char) {
+    catch_panic((), || {
+        if !value.is_null() {
+            drop(unsafe { CString::from_raw(value) });
+        }
+    })
+}
+
+/// Message for the most recent failure on this thread, or NULL if none
+///
+/// The pointer stays valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn t2v_last_error() -> *const c_char {
+    catch_panic(ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
@@ -0,0 +1,111 @@
+use crate::ui::{print_info, print_stats};
+use std::fs::{self, File, Metadata};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use text_to_voice::encoding::Encoding;
+use text_to_voice::text::Pipeline;
+use text_to_voice::{SpeechConfig, TextToSpeech, TtsError, TtsResult};
+
+/// How often the followed file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watch a file like `tail -f` and speak every complete line appended after `offset`
+///
+/// Truncation restarts from the beginning of the file, and so does rotation
+/// (the path now pointing at a different file). New lines are decoded with
+/// `encoding` and run through `pipeline`. Runs until interrupted.
+pub fn follow_file(
+    path: &Path,
+    offset: u64,
+    encoding: &'static Encoding,
+    tts_engine: &dyn TextToSpeech,
+    pipeline: &Pipeline,
+    speech_config: &SpeechConfig,
+) -> TtsResult<()> {
+    // Lines are split on the newline byte, which UTF-16 does not use
+    if !encoding.is_ascii_compatible() {
+        return Err(TtsError::ConfigError(format!(
+            "--follow does not support {} files",
+            encoding.name()
+        )));
+    }
+
+    print_info(&format!(
+        "Following {} for new lines (Ctrl+C to stop)...",
+        path.display()
+    ));
+
+    let mut position = offset;
+    let mut identity = fs::metadata(path)
+        .ok()
+        .and_then(|meta| file_identity(&meta));
+    let mut pending: Vec<u8> = Vec::new();
+    let mut lines_spoken: u64 = 0;
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            // The file is briefly missing while it is being rotated
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(TtsError::FileError(format!(
+                    "Cannot follow file '{}': {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        let current_identity = file_identity(&metadata);
+        if current_identity != identity {
+            print_info("File was rotated; reading the new file from the start");
+            identity = current_identity;
+            position = 0;
+            pending.clear();
+        } else if metadata.len() < position {
+            print_info("File was truncated; reading from the start");
+            position = 0;
+            pending.clear();
+        }
+
+        if metadata.len() == position {
+            continue;
+        }
+
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(position))?;
+        let read = file.read_to_end(&mut pending)?;
+        position += read as u64;
+
+        // Speak complete lines only; a partial last line waits for its newline
+        while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let (line, _) = encoding.decode_without_bom_handling(&line);
+            let line = pipeline.run(&line);
+            if line.is_empty() {
+                continue;
+            }
+            tts_engine.speak(&line, speech_config)?;
+            lines_spoken += 1;
+            print_stats(&format!("Lines spoken while following: {}", lines_spoken));
+        }
+    }
+}
+
+/// Identify the file behind a path so rotation can be detected
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Identify the file behind a path so rotation can be detected
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    // Without inode numbers, rotation is only noticed through truncation
+    None
+}
@@ -0,0 +1,525 @@
+//! Source code reading: only the comments and docstrings, or the whole code
+//! with identifiers split into words and symbols read by name
+
+use super::SpeechWriter;
+use crate::errors::{TtsError, TtsResult};
+use std::path::Path;
+
+/// Comment and string syntax of a family of languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// C, C++, Rust, Java, JavaScript, TypeScript, Go, Swift, Kotlin, C#...
+    CLike,
+    Python,
+    /// Shell, Ruby, Perl, R and other languages with `#` comments
+    Hash,
+    /// SQL and Haskell: `--` line comments
+    DoubleDash,
+    Lua,
+    /// Lisp family: `;` comments
+    Lisp,
+    /// Unknown language: `//`, `/* */` and `#` comments are all recognized
+    Generic,
+}
+
+impl Language {
+    /// Pick a language from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())?;
+        Some(match extension.as_str() {
+            "rs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "java" | "js" | "mjs"
+            | "cjs" | "jsx" | "ts" | "tsx" | "go" | "swift" | "kt" | "kts" | "cs" | "scala"
+            | "dart" | "php" | "m" | "zig" | "css" | "scss" => Language::CLike,
+            "py" | "pyi" | "pyw" => Language::Python,
+            "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "pm" | "r" | "ps1" | "cmake" | "nim"
+            | "jl" | "ex" | "exs" => Language::Hash,
+            "sql" | "hs" | "elm" => Language::DoubleDash,
+            "lua" => Language::Lua,
+            "lisp" | "el" | "clj" | "cljs" | "scm" | "rkt" => Language::Lisp,
+            _ => return None,
+        })
+    }
+
+    fn syntax(&self) -> Syntax {
+        match self {
+            Language::CLike => Syntax {
+                line: &["//"],
+                block: &[("/*", "*/")],
+                triple_quotes: false,
+                char_literals: true,
+            },
+            Language::Python => Syntax {
+                line: &["#"],
+                block: &[],
+                triple_quotes: true,
+                char_literals: false,
+            },
+            Language::Hash => Syntax {
+                line: &["#"],
+                block: &[],
+                triple_quotes: false,
+                char_literals: false,
+            },
+            Language::DoubleDash => Syntax {
+                line: &["--"],
+                block: &[("/*", "*/"), ("{-", "-}")],
+                triple_quotes: false,
+                char_literals: false,
+            },
+            Language::Lua => Syntax {
+                line: &["--"],
+                block: &[("--[[", "]]")],
+                triple_quotes: false,
+                char_literals: false,
+            },
+            Language::Lisp => Syntax {
+                line: &[";"],
+                block: &[("#|", "|#")],
+                triple_quotes: false,
+                char_literals: false,
+            },
+            Language::Generic => Syntax {
+                line: &["//", "#"],
+                block: &[("/*", "*/")],
+                triple_quotes: true,
+                char_literals: false,
+            },
+        }
+    }
+}
+
+/// What is read from source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceMode {
+    /// Only comments and docstrings
+    #[default]
+    Comments,
+    /// All of the code, with comments announced
+    Full,
+}
+
+impl SourceMode {
+    /// Parse the value given to `--code`
+    pub fn parse(value: &str) -> TtsResult<Self> {
+        match value {
+            "comments" => Ok(SourceMode::Comments),
+            "full" => Ok(SourceMode::Full),
+            other => Err(TtsError::ConfigError(format!(
+                "Unknown code reading mode '{}' (expected comments or full)",
+                other
+            ))),
+        }
+    }
+}
+
+struct Syntax {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+    /// Python-style `"""` strings, read as docstrings when they start a line
+    triple_quotes: bool,
+    /// `'x'` is a character literal, while a lone `'` is a lifetime or label
+    char_literals: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Comment(String),
+    Docstring(String),
+    Str(String),
+    Identifier(String),
+    Number(String),
+    Symbol(&'static str),
+    Other(char),
+    Newline,
+}
+
+/// Operators read as one symbol, longest first
+const OPERATORS: &[(&str, &str)] = &[
+    ("...", "dot dot dot"),
+    ("..=", "range to"),
+    ("===", "strictly equals"),
+    ("!==", "strictly not equals"),
+    ("<<=", "shift left equals"),
+    (">>=", "shift right equals"),
+    ("->", "arrow"),
+    ("=>", "fat arrow"),
+    ("::", "colon colon"),
+    ("==", "equals equals"),
+    ("!=", "not equals"),
+    ("<=", "less than or equal"),
+    (">=", "greater than or equal"),
+    ("&&", "and"),
+    ("||", "or"),
+    ("+=", "plus equals"),
+    ("-=", "minus equals"),
+    ("*=", "times equals"),
+    ("/=", "divide equals"),
+    ("%=", "mod equals"),
+    ("|=", "or equals"),
+    ("&=", "and equals"),
+    ("^=", "xor equals"),
+    ("<<", "shift left"),
+    (">>", "shift right"),
+    ("++", "plus plus"),
+    ("--", "minus minus"),
+    ("**", "power"),
+    ("//", "floor divide"),
+    (":=", "colon equals"),
+    ("..", "range"),
+    ("{", "open brace"),
+    ("}", "close brace"),
+    ("(", "open paren"),
+    (")", "close paren"),
+    ("[", "open bracket"),
+    ("]", "close bracket"),
+    ("<", "less than"),
+    (">", "greater than"),
+    ("=", "equals"),
+    ("+", "plus"),
+    ("-", "minus"),
+    ("*", "star"),
+    ("/", "slash"),
+    ("%", "percent"),
+    ("!", "not"),
+    ("?", "question mark"),
+    ("&", "ampersand"),
+    ("|", "pipe"),
+    ("^", "caret"),
+    ("~", "tilde"),
+    (".", "dot"),
+    (":", "colon"),
+    ("#", "hash"),
+    ("@", "at"),
+    ("$", "dollar"),
+    ("\\", "backslash"),
+    ("'", "tick"),
+    (",", ","),
+    (";", ";"),
+];
+
+/// Keywords read as the word they stand for
+const KEYWORDS: &[(&str, &str)] = &[
+    ("fn", "function"),
+    ("func", "function"),
+    ("def", "define"),
+    ("impl", "implement"),
+    ("pub", "public"),
+    ("mut", "mutable"),
+    ("const", "constant"),
+    ("var", "variable"),
+    ("elif", "else if"),
+    ("str", "string"),
+    ("usize", "u size"),
+    ("isize", "i size"),
+    ("bool", "boolean"),
+    ("args", "arguments"),
+    ("kwargs", "keyword arguments"),
+    ("init", "initialize"),
+];
+
+/// Read source code in the given language
+pub fn to_speech(source: &str, language: Language, mode: SourceMode) -> String {
+    let tokens = tokenize(source, &language.syntax());
+    let mut writer = SpeechWriter::new();
+    match mode {
+        SourceMode::Comments => read_comments(&mut writer, &tokens),
+        SourceMode::Full => read_code(&mut writer, &tokens),
+    }
+    writer.finish()
+}
+
+/// Comments and docstrings as paragraphs; a run of line comments is one paragraph
+fn read_comments(writer: &mut SpeechWriter, tokens: &[Token]) {
+    let mut newlines = 0;
+    let mut in_run = false;
+    for token in tokens {
+        match token {
+            Token::Comment(text) | Token::Docstring(text) => {
+                if !in_run || newlines > 1 {
+                    writer.paragraph_break();
+                }
+                writer.inline(&clean_comment(text));
+                writer.inline(" ");
+                in_run = true;
+                newlines = 0;
+            }
+            Token::Newline => newlines += 1,
+            _ => {
+                if in_run {
+                    writer.paragraph_break();
+                }
+                in_run = false;
+            }
+        }
+    }
+}
+
+/// The whole code, line by line
+fn read_code(writer: &mut SpeechWriter, tokens: &[Token]) {
+    let mut newlines = 0;
+    for token in tokens {
+        if *token != Token::Newline {
+            match newlines {
+                0 => {}
+                1 => writer.line_break(),
+                _ => writer.paragraph_break(),
+            }
+            newlines = 0;
+        }
+        match token {
+            Token::Newline => newlines += 1,
+            Token::Comment(text) => {
+                writer.line_break();
+                writer.text("Comment:");
+                writer.inline(" ");
+                writer.inline(&clean_comment(text));
+                writer.line_break();
+            }
+            Token::Docstring(text) => {
+                writer.line_break();
+                writer.text("Docstring:");
+                writer.inline(" ");
+                writer.inline(&clean_comment(text));
+                writer.line_break();
+            }
+            Token::Str(text) => {
+                writer.text("string");
+                writer.inline(" ");
+                if text.trim().is_empty() {
+                    writer.text("empty");
+                } else {
+                    writer.inline(text);
+                }
+                writer.separator();
+            }
+            Token::Identifier(name) => writer.text(&speak_identifier(name)),
+            Token::Number(number) => writer.text(number),
+            Token::Symbol(",") | Token::Symbol(";") => writer.separator(),
+            Token::Symbol(symbol) => writer.text(symbol),
+            Token::Other(c) => writer.text(&c.to_string()),
+        }
+    }
+}
+
+/// Split an identifier into words: `parseCargoToml` and `parse_cargo_toml`
+/// are both "parse cargo toml", and `HTTPServer` is "HTTP server"
+pub(crate) fn split_identifier(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (index, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(index + 1);
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_ascii_digit()
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    // Acronyms keep their capitals so they are spelled out
+    words
+        .into_iter()
+        .map(|word| {
+            if word.len() > 1 && word.chars().all(|c| c.is_uppercase() || c.is_ascii_digit()) {
+                word
+            } else {
+                word.to_lowercase()
+            }
+        })
+        .collect()
+}
+
+fn speak_identifier(name: &str) -> String {
+    if let Some((_, word)) = KEYWORDS.iter().find(|(keyword, _)| *keyword == name) {
+        return word.to_string();
+    }
+    split_identifier(name).join(" ")
+}
+
+/// Strip comment markers and decoration, and split identifiers in backticks
+fn clean_comment(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let line = line
+            .trim()
+            .trim_start_matches(['*', '/', '!', '#', '-', ';'])
+            .trim();
+        // Rulers such as "=======" or "-------"
+        if line.chars().all(|c| "=-*#/_~+".contains(c)) {
+            continue;
+        }
+        lines.push(line);
+    }
+
+    let joined = lines.join(" ");
+    let mut out = String::new();
+    for (index, part) in joined.split('`').enumerate() {
+        if index % 2 == 1 && !part.contains(' ') {
+            out.push_str(&split_identifier(part).join(" "));
+        } else {
+            out.push_str(part);
+        }
+    }
+    out
+}
+
+fn tokenize(source: &str, syntax: &Syntax) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    let starts_with = |index: usize, pattern: &str| {
+        pattern
+            .chars()
+            .enumerate()
+            .all(|(offset, c)| chars.get(index + offset) == Some(&c))
+    };
+    let line_start = |tokens: &[Token]| matches!(tokens.last(), None | Some(Token::Newline));
+
+    // A shebang line is not a comment worth reading
+    if starts_with(0, "#!") {
+        while index < chars.len() && chars[index] != '\n' {
+            index += 1;
+        }
+    }
+
+    'outer: while index < chars.len() {
+        let c = chars[index];
+        if c == '\n' {
+            tokens.push(Token::Newline);
+            index += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        for (open, close) in syntax.block {
+            if starts_with(index, open) {
+                let start = index + open.chars().count();
+                let mut end = start;
+                while end < chars.len() && !starts_with(end, close) {
+                    end += 1;
+                }
+                tokens.push(Token::Comment(chars[start..end].iter().collect()));
+                index = (end + close.chars().count()).min(chars.len());
+                continue 'outer;
+            }
+        }
+        for marker in syntax.line {
+            if starts_with(index, marker) {
+                let start = index + marker.chars().count();
+                let mut end = start;
+                while end < chars.len() && chars[end] != '\n' {
+                    end += 1;
+                }
+                tokens.push(Token::Comment(chars[start..end].iter().collect()));
+                index = end;
+                continue 'outer;
+            }
+        }
+
+        if syntax.triple_quotes && (starts_with(index, "\"\"\"") || starts_with(index, "'''")) {
+            let quote: String = chars[index..index + 3].iter().collect();
+            let start = index + 3;
+            let mut end = start;
+            while end < chars.len() && !starts_with(end, &quote) {
+                end += 1;
+            }
+            let text: String = chars[start..end].iter().collect();
+            if line_start(&tokens) {
+                tokens.push(Token::Docstring(text));
+            } else {
+                tokens.push(Token::Str(text));
+            }
+            index = (end + 3).min(chars.len());
+            continue;
+        }
+
+        let is_char_literal = c == '\''
+            && (!syntax.char_literals
+                || chars.get(index + 2) == Some(&'\'')
+                || (chars.get(index + 1) == Some(&'\\')
+                    && chars[index + 2..].iter().take(8).any(|&c| c == '\'')));
+        if c == '"' || c == '`' || is_char_literal {
+            let mut end = index + 1;
+            let mut text = String::new();
+            while end < chars.len() && chars[end] != c {
+                if chars[end] == '\\' && end + 1 < chars.len() {
+                    end += 1;
+                }
+                // Unterminated strings end at the line, except template literals
+                if chars[end] == '\n' && c != '`' {
+                    break;
+                }
+                text.push(chars[end]);
+                end += 1;
+            }
+            tokens.push(Token::Str(text));
+            index = end + 1;
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+            // String prefixes such as Python's f"..." and Rust's b"..."
+            let prefix = word.len() <= 2
+                && word.chars().all(|c| "rbfuRBFU".contains(c))
+                && chars.get(index).is_some_and(|&c| c == '"' || c == '\'');
+            if !prefix {
+                tokens.push(Token::Identifier(word));
+            }
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let start = index;
+            while index < chars.len()
+                && (chars[index].is_alphanumeric()
+                    || chars[index] == '_'
+                    || (chars[index] == '.'
+                        && chars.get(index + 1).is_some_and(|c| c.is_ascii_digit())))
+            {
+                index += 1;
+            }
+            tokens.push(Token::Number(chars[start..index].iter().collect()));
+            continue;
+        }
+
+        match OPERATORS
+            .iter()
+            .find(|(operator, _)| starts_with(index, operator))
+        {
+            Some((operator, spoken)) => {
+                tokens.push(Token::Symbol(spoken));
+                index += operator.chars().count();
+            }
+            None => {
+                tokens.push(Token::Other(c));
+                index += 1;
+            }
+        }
+    }
+    tokens
+}
@@ -0,0 +1,124 @@
+//! CSV and TSV reading: each row read with its column headers,
+//! e.g. "Row 1: Name: Alice, Role: Engineer."
+
+use super::ReadOptions;
+use crate::errors::{TtsError, TtsResult};
+use csv::ReaderBuilder;
+
+/// Delimiters tried when sniffing the header line, in order of preference
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Read the selected rows and columns; returns the text and stats about the selection
+pub fn to_speech(source: &str, options: &ReadOptions) -> TtsResult<(String, Vec<String>)> {
+    let delimiter = sniff_delimiter(source);
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(source.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(invalid)?
+        .iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+    let columns = select_columns(&headers, &options.columns)?;
+
+    let mut lines = Vec::new();
+    let mut total = 0;
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(invalid)?;
+        let row = index as u32 + 1;
+        total = row;
+        if options.rows.is_some_and(|range| !range.contains(row)) {
+            continue;
+        }
+
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|&column| {
+                let value = record.get(column).map(str::trim).unwrap_or_default();
+                let value = if value.is_empty() { "empty" } else { value };
+                match headers.get(column).filter(|header| !header.is_empty()) {
+                    Some(header) => format!("{}: {}", header, value),
+                    None => format!("Column {}: {}", column + 1, value),
+                }
+            })
+            .collect();
+        lines.push(format!("Row {}: {}.", row, cells.join(", ")));
+    }
+
+    if lines.is_empty() {
+        return Err(TtsError::ConfigError(format!(
+            "No rows selected (the table has {} rows)",
+            total
+        )));
+    }
+
+    let delimiter_name = match delimiter {
+        b'\t' => "tab",
+        b';' => "semicolon",
+        b'|' => "pipe",
+        _ => "comma",
+    };
+    let mut stats = vec![format!(
+        "Rows: {} of {} ({}-separated)",
+        lines.len(),
+        total,
+        delimiter_name
+    )];
+    if !options.columns.is_empty() {
+        let names: Vec<&str> = columns.iter().map(|&c| headers[c].as_str()).collect();
+        stats.push(format!("Columns: {}", names.join(", ")));
+    }
+    Ok((lines.join("\n"), stats))
+}
+
+/// Pick the delimiter that splits the header line into the most fields
+fn sniff_delimiter(source: &str) -> u8 {
+    let header = source.lines().next().unwrap_or_default();
+    DELIMITERS
+        .iter()
+        .copied()
+        .max_by_key(|&delimiter| {
+            // Ties go to the earlier delimiter
+            let count = header.bytes().filter(|&b| b == delimiter).count();
+            (
+                count,
+                std::cmp::Reverse(DELIMITERS.iter().position(|&d| d == delimiter)),
+            )
+        })
+        .unwrap_or(b',')
+}
+
+/// Resolve `--columns` names (case-insensitive) or 1-based numbers to indices
+fn select_columns(headers: &[String], wanted: &[String]) -> TtsResult<Vec<usize>> {
+    if wanted.is_empty() {
+        return Ok((0..headers.len()).collect());
+    }
+    wanted
+        .iter()
+        .map(|name| {
+            let by_name = headers
+                .iter()
+                .position(|header| header.eq_ignore_ascii_case(name.trim()));
+            let by_number = name
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n >= 1 && n <= headers.len())
+                .map(|n| n - 1);
+            by_name.or(by_number).ok_or_else(|| {
+                TtsError::ConfigError(format!(
+                    "Unknown column '{}' (the columns are: {})",
+                    name,
+                    headers.join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+fn invalid(error: csv::Error) -> TtsError {
+    TtsError::FileError(format!("Invalid CSV: {}", error))
+}
@@ -0,0 +1,159 @@
+//! Email reading: single RFC 5322 messages (`.eml`) and mbox mailboxes
+//!
+//! Each message is announced by sender, subject and date. Plain text parts
+//! are preferred over HTML, and quoted replies and signatures are left out.
+
+use super::{Document, ReadOptions, SpeechWriter, html};
+use crate::errors::{TtsError, TtsResult};
+use mail_parser::mailbox::mbox::MessageIterator;
+use mail_parser::{DateTime, Message, MessageParser, MimeHeaders};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Read a message, or every message of an mbox file
+pub fn to_speech(bytes: &[u8], options: &ReadOptions) -> TtsResult<Document> {
+    let parser = MessageParser::default();
+    let raw_messages: Vec<Vec<u8>> = if bytes.starts_with(b"From ") {
+        MessageIterator::new(bytes)
+            .filter_map(Result::ok)
+            .map(|message| message.unwrap_contents())
+            .collect()
+    } else {
+        vec![bytes.to_vec()]
+    };
+
+    let mut writer = SpeechWriter::new();
+    let mut trimmed = 0;
+    let total = raw_messages.len();
+    for (index, raw) in raw_messages.iter().enumerate() {
+        let message = parser
+            .parse(raw)
+            .ok_or_else(|| TtsError::FileError("Invalid email message".to_string()))?;
+        if total > 1 {
+            writer.paragraph_break();
+            writer.text(&format!("Message {} of {}.", index + 1, total));
+        }
+        if read_message(&mut writer, &message, options) {
+            trimmed += 1;
+        }
+    }
+
+    if total == 0 {
+        return Err(TtsError::FileError("Mailbox has no messages".to_string()));
+    }
+    let mut stats = vec![format!("Messages: {}", total)];
+    if trimmed > 0 {
+        stats.push(format!(
+            "Left out quoted replies or signatures in {} of them",
+            trimmed
+        ));
+    }
+    Ok(Document {
+        text: writer.finish(),
+        encoding: None,
+        stats,
+        item_ids: Vec::new(),
+    })
+}
+
+/// Announce the headers and read the body; returns whether anything was trimmed
+fn read_message(writer: &mut SpeechWriter, message: &Message, options: &ReadOptions) -> bool {
+    writer.paragraph_break();
+    if let Some(sender) = message.from().and_then(|from| from.first()) {
+        let name = sender
+            .name()
+            .or(sender.address())
+            .unwrap_or("unknown sender");
+        writer.text(&format!("From {}", name));
+        writer.line_break();
+    }
+    let subject = message.subject().map(str::trim).filter(|s| !s.is_empty());
+    writer.text(&format!("Subject: {}", subject.unwrap_or("none")));
+    writer.line_break();
+    if let Some(date) = message.date() {
+        writer.text(&format!("Sent {}", speakable_date(date)));
+        writer.line_break();
+    }
+
+    // Text bodies are the plain parts, or the HTML part when there is no plain one
+    let mut trimmed = false;
+    for part in message.text_bodies() {
+        let Some(content) = part.text_contents() else {
+            continue;
+        };
+        let text = if part.is_text_html() {
+            html::to_speech(content, options)
+        } else {
+            content.to_string()
+        };
+        let (body, cut) = strip_replies(&text);
+        trimmed |= cut;
+        for paragraph in body.split("\n\n") {
+            writer.paragraph_break();
+            writer.inline(paragraph);
+        }
+    }
+
+    let attachments: Vec<&str> = message
+        .attachments()
+        .filter_map(|part| part.attachment_name())
+        .collect();
+    if !attachments.is_empty() {
+        writer.paragraph_break();
+        writer.text(&format!("Attachments: {}", attachments.join(", ")));
+    }
+    writer.paragraph_break();
+    trimmed
+}
+
+/// Drop quoted lines, and cut the body at a signature or a quoted reply header
+///
+/// Returns the remaining text and whether anything was removed.
+fn strip_replies(body: &str) -> (String, bool) {
+    let mut kept = Vec::new();
+    let mut cut = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        let ends_body = line == "-- "
+            || trimmed == "--"
+            || trimmed.starts_with("-----Original Message-----")
+            || (trimmed.len() >= 10 && trimmed.chars().all(|c| c == '_'))
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"))
+            || trimmed.starts_with("Sent from my ");
+        if ends_body {
+            cut = true;
+            break;
+        }
+        if trimmed.starts_with('>') {
+            cut = true;
+            continue;
+        }
+        kept.push(line);
+    }
+    (kept.join("\n"), cut)
+}
+
+/// "12 March 2024 at 14:05"
+pub(super) fn speakable_date(date: &DateTime) -> String {
+    let month = MONTHS
+        .get((date.month as usize).wrapping_sub(1))
+        .copied()
+        .unwrap_or_default();
+    format!(
+        "{} {} {} at {:02}:{:02}",
+        date.day, month, date.year, date.hour, date.minute
+    )
+}
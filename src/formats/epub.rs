@@ -0,0 +1,277 @@
+//! EPUB reading: chapters in spine order, titled from the table of contents
+
+use super::{ReadOptions, html};
+use crate::errors::{TtsError, TtsResult};
+use roxmltree::{Document, Node, ParsingOptions};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+const OPS_NAMESPACE: &str = "http://www.idpf.org/2007/ops";
+
+/// One chapter of a book, in reading order
+#[derive(Debug, Clone)]
+pub struct Chapter {
+    pub title: String,
+    /// Path of the chapter's XHTML file inside the archive
+    pub path: String,
+}
+
+/// An opened EPUB file
+pub struct EpubBook {
+    archive: ZipArchive<Cursor<Vec<u8>>>,
+    pub title: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl EpubBook {
+    /// Open an EPUB from its bytes and read its spine and table of contents
+    pub fn open(bytes: Vec<u8>) -> TtsResult<Self> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))
+            .map_err(|e| invalid(&format!("not a ZIP archive ({})", e)))?;
+
+        let container = read_entry(&mut archive, "META-INF/container.xml")?;
+        let container = parse_xml(&container)?;
+        let opf_path = container
+            .descendants()
+            .find(|node| node.has_tag_name("rootfile"))
+            .and_then(|node| node.attribute("full-path"))
+            .ok_or_else(|| invalid("container.xml does not name a package file"))?
+            .to_string();
+        let base = parent_dir(&opf_path);
+
+        let opf = read_entry(&mut archive, &opf_path)?;
+        let opf = parse_xml(&opf)?;
+
+        let title = opf
+            .descendants()
+            .find(|node| node.has_tag_name("title"))
+            .and_then(|node| node.text())
+            .map(|title| title.trim().to_string());
+
+        // Manifest: id -> (path, properties)
+        let mut manifest = HashMap::new();
+        for item in opf.descendants().filter(|node| node.has_tag_name("item")) {
+            if let (Some(id), Some(href)) = (item.attribute("id"), item.attribute("href")) {
+                let properties = item.attribute("properties").unwrap_or_default();
+                manifest.insert(id, (resolve(&base, href), properties));
+            }
+        }
+
+        // Table of contents: EPUB 3 nav document, or the EPUB 2 NCX named by the spine
+        let spine = opf
+            .descendants()
+            .find(|node| node.has_tag_name("spine"))
+            .ok_or_else(|| invalid("package file has no spine"))?;
+        let nav_path = manifest
+            .values()
+            .find(|(_, properties)| properties.split_whitespace().any(|p| p == "nav"))
+            .map(|(path, _)| path.clone());
+        let ncx_path = spine
+            .attribute("toc")
+            .and_then(|id| manifest.get(id))
+            .map(|(path, _)| path.clone());
+
+        let mut titles = HashMap::new();
+        if let Some(nav_path) = nav_path {
+            let nav = read_entry(&mut archive, &nav_path)?;
+            titles = nav_titles(&nav, &parent_dir(&nav_path))?;
+        } else if let Some(ncx_path) = ncx_path {
+            let ncx = read_entry(&mut archive, &ncx_path)?;
+            titles = ncx_titles(&ncx, &parent_dir(&ncx_path))?;
+        }
+
+        let chapters = spine
+            .children()
+            .filter(|node| node.has_tag_name("itemref"))
+            .filter(|node| node.attribute("linear") != Some("no"))
+            .filter_map(|node| manifest.get(node.attribute("idref")?))
+            .map(|(path, _)| Chapter {
+                title: titles
+                    .get(path.as_str())
+                    .cloned()
+                    .unwrap_or_else(|| untitled(path)),
+                path: path.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        if chapters.is_empty() {
+            return Err(invalid("the spine lists no chapters"));
+        }
+
+        Ok(EpubBook {
+            archive,
+            title,
+            chapters,
+        })
+    }
+
+    /// Text of one chapter, by index into `chapters`
+    pub fn chapter_text(&mut self, index: usize, options: &ReadOptions) -> TtsResult<String> {
+        let chapter = self.chapters.get(index).ok_or_else(|| {
+            TtsError::ConfigError(format!(
+                "Chapter {} does not exist (the book has {} chapters)",
+                index + 1,
+                self.chapters.len()
+            ))
+        })?;
+        let path = chapter.path.clone();
+        let content = read_entry(&mut self.archive, &path)?;
+        Ok(html::to_speech(&content, options))
+    }
+}
+
+/// Read the book, or only `options.chapter` when one is selected
+pub fn to_speech(bytes: &[u8], options: &ReadOptions) -> TtsResult<String> {
+    let mut book = EpubBook::open(bytes.to_vec())?;
+
+    if let Some(chapter) = options.chapter {
+        return book.chapter_text(chapter.saturating_sub(1), options);
+    }
+
+    let mut text = String::new();
+    if let Some(title) = &book.title {
+        text.push_str(&format!("{}.\n\n", title));
+    }
+    for index in 0..book.chapters.len() {
+        text.push_str(&book.chapter_text(index, options)?);
+        text.push_str("\n\n");
+    }
+    Ok(text)
+}
+
+/// Titles from an EPUB 3 navigation document, keyed by chapter path
+fn nav_titles(source: &str, base: &str) -> TtsResult<HashMap<String, String>> {
+    let document = parse_xml(source)?;
+    let nav = document
+        .descendants()
+        .filter(|node| node.has_tag_name("nav"))
+        .find(|node| node.attribute((OPS_NAMESPACE, "type")) == Some("toc"))
+        .or_else(|| document.descendants().find(|node| node.has_tag_name("nav")));
+
+    let mut titles = HashMap::new();
+    if let Some(nav) = nav {
+        for link in nav.descendants().filter(|node| node.has_tag_name("a")) {
+            if let Some(href) = link.attribute("href") {
+                titles
+                    .entry(resolve(base, href))
+                    .or_insert_with(|| node_text(link));
+            }
+        }
+    }
+    Ok(titles)
+}
+
+/// Titles from an EPUB 2 NCX file, keyed by chapter path
+fn ncx_titles(source: &str, base: &str) -> TtsResult<HashMap<String, String>> {
+    let document = parse_xml(source)?;
+    let mut titles = HashMap::new();
+
+    for point in document
+        .descendants()
+        .filter(|node| node.has_tag_name("navPoint"))
+    {
+        let label = point
+            .children()
+            .find(|node| node.has_tag_name("navLabel"))
+            .map(node_text);
+        let src = point
+            .children()
+            .find(|node| node.has_tag_name("content"))
+            .and_then(|node| node.attribute("src"));
+        if let (Some(label), Some(src)) = (label, src) {
+            titles.entry(resolve(base, src)).or_insert(label);
+        }
+    }
+    Ok(titles)
+}
+
+/// All text inside a node, with whitespace collapsed
+fn node_text(node: Node) -> String {
+    node.descendants()
+        .filter(|node| node.is_text())
+        .filter_map(|node| node.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fallback title for a chapter missing from the table of contents
+fn untitled(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    stem.replace(['_', '-'], " ")
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, path: &str) -> TtsResult<String> {
+    let mut entry = archive
+        .by_name(path)
+        .map_err(|_| invalid(&format!("missing '{}'", path)))?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| invalid(&format!("cannot read '{}' ({})", path, e)))?;
+    Ok(content)
+}
+
+fn parse_xml(source: &str) -> TtsResult<Document<'_>> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    Document::parse_with_options(source, options).map_err(|e| invalid(&e.to_string()))
+}
+
+fn invalid(reason: &str) -> TtsError {
+    TtsError::FileError(format!("Invalid EPUB: {}", reason))
+}
+
+/// Directory part of an archive path, with a trailing slash when not empty
+fn parent_dir(path: &str) -> String {
+    path.rsplit_once('/')
+        .map(|(dir, _)| format!("{}/", dir))
+        .unwrap_or_default()
+}
+
+/// Resolve an href against a base directory: drop the fragment, decode %XX and fold `..`
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let joined = format!("{}{}", base, percent_decode(href));
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in joined.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,262 @@
+//! RSS 2.0 and Atom feed reading: the feed title, then each item's title,
+//! date and description with its HTML stripped, newest first.
+//!
+//! Items can be limited to the latest few, and a heard file remembers which
+//! items were already read so a saved feed can be replayed as a briefing.
+
+use super::email::speakable_date;
+use super::{Document, ReadOptions, SpeechWriter, html};
+use crate::errors::{TtsError, TtsResult};
+use mail_parser::DateTime;
+use roxmltree::Node;
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+/// One entry of a feed
+struct Item {
+    /// The item's guid or id, falling back to its link or title
+    id: String,
+    title: String,
+    date: Option<DateTime>,
+    /// Description or content, as HTML
+    description: String,
+}
+
+/// Read the feed title and its unheard items; the ids of the items read are
+/// returned in the document so they can be remembered once spoken
+pub fn to_speech(source: &str, options: &ReadOptions) -> TtsResult<Document> {
+    let xml = roxmltree::Document::parse(source)
+        .map_err(|e| TtsError::FileError(format!("Invalid feed: {}", e)))?;
+    let root = xml.root_element();
+    let (title, mut items) = match root.tag_name().name() {
+        "rss" => {
+            let channel = child(root, "channel")
+                .ok_or_else(|| TtsError::FileError("Invalid feed: no channel".to_string()))?;
+            (child_text(channel, "title"), rss_items(channel))
+        }
+        "feed" => (child_text(root, "title"), atom_items(root)),
+        other => {
+            return Err(TtsError::FileError(format!(
+                "Invalid feed: root element is <{}>, expected <rss> or <feed>",
+                other
+            )));
+        }
+    };
+    let title = title.unwrap_or_else(|| "Untitled feed".to_string());
+    let total = items.len();
+
+    let heard = match &options.heard {
+        Some(path) => load_heard(path)?,
+        None => HashSet::new(),
+    };
+    items.retain(|item| !heard.contains(&item.id));
+    let unheard = items.len();
+
+    // Undated items keep their feed order after the dated ones
+    items.sort_by_key(|item| std::cmp::Reverse(item.date.as_ref().map(DateTime::to_timestamp)));
+    if let Some(latest) = options.latest {
+        items.truncate(latest);
+    }
+
+    let mut writer = SpeechWriter::new();
+    writer.text(&format!("Feed: {}", title));
+    writer.line_break();
+    if items.is_empty() {
+        writer.text(if total == 0 {
+            "No items."
+        } else {
+            "No new items."
+        });
+    } else {
+        writer.text(&format!(
+            "{} {}.",
+            items.len(),
+            match (items.len(), options.heard.is_some()) {
+                (1, true) => "new item",
+                (1, false) => "item",
+                (_, true) => "new items",
+                (_, false) => "items",
+            }
+        ));
+    }
+    for (index, item) in items.iter().enumerate() {
+        writer.paragraph_break();
+        writer.text(&format!(
+            "Item {} of {}: {}",
+            index + 1,
+            items.len(),
+            item.title
+        ));
+        writer.line_break();
+        if let Some(date) = &item.date {
+            writer.text(&format!("Published {}", speakable_date(date)));
+            writer.line_break();
+        }
+        let description = html::to_speech(&item.description, options);
+        if !description.trim().is_empty() {
+            writer.paragraphs(&description);
+        }
+    }
+
+    let mut stats = vec![format!("Feed: {}", title)];
+    let mut counts = format!("Items: {} of {}", items.len(), total);
+    if options.heard.is_some() {
+        counts.push_str(&format!(" ({} already heard)", total - unheard));
+    }
+    stats.push(counts);
+
+    Ok(Document {
+        text: writer.finish(),
+        encoding: None,
+        stats,
+        item_ids: items.into_iter().map(|item| item.id).collect(),
+    })
+}
+
+/// Add the ids of items that were read to the heard file
+pub fn remember_heard(path: &Path, ids: &[String]) -> TtsResult<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let error = |e: std::io::Error| {
+        TtsError::FileError(format!(
+            "Cannot update heard file '{}': {}",
+            path.display(),
+            e
+        ))
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(error)?;
+    for id in ids {
+        writeln!(file, "{}", id).map_err(error)?;
+    }
+    Ok(())
+}
+
+/// Ids already heard, one per line; a missing file means nothing was heard yet
+fn load_heard(path: &Path) -> TtsResult<HashSet<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(TtsError::FileError(format!(
+            "Cannot read heard file '{}': {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+fn rss_items(channel: Node) -> Vec<Item> {
+    children(channel, "item")
+        .map(|node| {
+            let title = child_text(node, "title");
+            let link = child_text(node, "link");
+            let date = child_text(node, "pubDate")
+                .and_then(|date| DateTime::parse_rfc822(&date))
+                .or_else(|| child_text(node, "date").and_then(|d| DateTime::parse_rfc3339(&d)));
+            // content:encoded holds the full post when the description is a teaser
+            let description = child_text(node, "encoded")
+                .or_else(|| child_text(node, "description"))
+                .unwrap_or_default();
+            Item {
+                id: item_id(child_text(node, "guid"), link, &title, &date),
+                title: title.unwrap_or_else(|| "Untitled".to_string()),
+                date,
+                description,
+            }
+        })
+        .collect()
+}
+
+fn atom_items(feed: Node) -> Vec<Item> {
+    children(feed, "entry")
+        .map(|node| {
+            let title = child_text(node, "title");
+            let link = children(node, "link")
+                .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+                .and_then(|link| link.attribute("href"))
+                .map(str::to_string);
+            let date = child_text(node, "published")
+                .or_else(|| child_text(node, "updated"))
+                .and_then(|date| DateTime::parse_rfc3339(&date));
+            let description = child(node, "content")
+                .or_else(|| child(node, "summary"))
+                .map(atom_text)
+                .unwrap_or_default();
+            Item {
+                id: item_id(child_text(node, "id"), link, &title, &date),
+                title: title.unwrap_or_else(|| "Untitled".to_string()),
+                date,
+                description,
+            }
+        })
+        .collect()
+}
+
+/// Atom text constructs hold escaped HTML, inline XHTML or plain text;
+/// all of them are returned as HTML
+fn atom_text(node: Node) -> String {
+    let text = match node.attribute("type") {
+        Some("html") => return node_text(node),
+        Some("xhtml") => node
+            .descendants()
+            .filter_map(|n| n.text().filter(|_| n.is_text()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => node_text(node),
+    };
+    // Plain text must not be taken for markup
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn item_id(
+    id: Option<String>,
+    link: Option<String>,
+    title: &Option<String>,
+    date: &Option<DateTime>,
+) -> String {
+    id.or(link).unwrap_or_else(|| {
+        let date = date.as_ref().map(DateTime::to_rfc3339).unwrap_or_default();
+        format!("{} {}", title.as_deref().unwrap_or_default(), date)
+            .trim()
+            .to_string()
+    })
+}
+
+/// Child elements by local name, so namespaced elements such as
+/// `content:encoded` and `dc:date` match too
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn child_text(node: Node, name: &'static str) -> Option<String> {
+    child(node, name)
+        .map(node_text)
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// The text of an element, including CDATA sections
+fn node_text(node: Node) -> String {
+    node.children().filter_map(|n| n.text()).collect()
+}
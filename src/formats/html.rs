@@ -0,0 +1,471 @@
+//! HTML reading: headings, lists, tables and image alt text read as speech
+//!
+//! A small forgiving tokenizer is enough here; pages saved from browsers and
+//! generated API docs are rarely well-formed, and nothing needs a full DOM.
+
+use super::{CodeBlockMode, ListState, ReadOptions, SpeechWriter, describe_table, summarize_code};
+
+/// Elements whose content is never read
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "math", "nav", "iframe", "object",
+    "canvas", "select", "button",
+];
+
+/// Elements that never have content or an end tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements that start a new block of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "aside",
+    "blockquote",
+    "dl",
+    "dt",
+    "dd",
+    "figure",
+    "figcaption",
+    "address",
+    "details",
+    "summary",
+    "caption",
+    "form",
+    "fieldset",
+    "legend",
+];
+
+/// A piece of an HTML document
+#[derive(Debug)]
+enum Token {
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+    },
+    End(String),
+    Text(String),
+}
+
+/// A table being read, so its rows and columns can be announced
+struct TableState {
+    rows: usize,
+    columns: usize,
+    cells_in_row: usize,
+    start: usize,
+}
+
+/// Convert HTML source into text ready for speech
+pub fn to_speech(source: &str, options: &ReadOptions) -> String {
+    let mut writer = SpeechWriter::new();
+    let mut lists: Vec<ListState> = Vec::new();
+    let mut tables: Vec<TableState> = Vec::new();
+    let mut heading_start: Option<usize> = None;
+    let mut preformatted: Option<String> = None;
+    let mut skipping: Option<(String, usize)> = None;
+
+    for token in tokenize(source) {
+        // Inside a skipped element, only track nesting of the same element
+        if let Some((skipped, depth)) = &mut skipping {
+            match &token {
+                // The end of <head> is optional
+                Token::Start { name, .. } if skipped == "head" && name == "body" => {
+                    skipping = None;
+                }
+                Token::Start { name, .. } if name == skipped => *depth += 1,
+                Token::End(name) if name == skipped => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        skipping = None;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if let Some(code) = &mut preformatted {
+            match token {
+                Token::Text(text) => code.push_str(&text),
+                Token::End(name) if name == "pre" => {
+                    let code = preformatted.take().unwrap_or_default();
+                    read_preformatted(&mut writer, &code, options.code_blocks);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match token {
+            Token::Start { name, attributes } => {
+                let attribute = |key: &str| {
+                    attributes
+                        .iter()
+                        .find(|(name, _)| name == key)
+                        .map(|(_, value)| value.as_str())
+                };
+                let hidden = attribute("hidden").is_some()
+                    || attribute("aria-hidden") == Some("true")
+                    || attribute("role") == Some("navigation");
+
+                if (SKIPPED_ELEMENTS.contains(&name.as_str()) || hidden)
+                    && !VOID_ELEMENTS.contains(&name.as_str())
+                {
+                    skipping = Some((name, 1));
+                    continue;
+                }
+
+                match name.as_str() {
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                        finish_heading(&mut writer, &mut heading_start);
+                        writer.paragraph_break();
+                        heading_start = Some(writer.len());
+                    }
+                    "pre" => preformatted = Some(String::new()),
+                    "ul" | "ol" | "menu" => {
+                        writer.paragraph_break();
+                        lists.push(ListState {
+                            first_number: (name == "ol").then_some(1),
+                            items: 0,
+                            start: writer.len(),
+                        });
+                    }
+                    "li" => {
+                        writer.line_break();
+                        if let Some(list) = lists.last_mut() {
+                            list.items += 1;
+                            if let Some(number) = list.item_number() {
+                                writer.text(&format!("Item {}:", number));
+                            }
+                        }
+                    }
+                    "table" => {
+                        writer.paragraph_break();
+                        tables.push(TableState {
+                            rows: 0,
+                            columns: 0,
+                            cells_in_row: 0,
+                            start: writer.len(),
+                        });
+                    }
+                    "tr" => {
+                        writer.line_break();
+                        if let Some(table) = tables.last_mut() {
+                            table.rows += 1;
+                            table.cells_in_row = 0;
+                        }
+                    }
+                    "td" | "th" => {
+                        if let Some(table) = tables.last_mut() {
+                            if table.cells_in_row > 0 {
+                                writer.separator();
+                            }
+                            table.cells_in_row += 1;
+                            table.columns = table.columns.max(table.cells_in_row);
+                        }
+                    }
+                    "img" => {
+                        if let Some(alt) = attribute("alt").map(str::trim)
+                            && !alt.is_empty()
+                        {
+                            writer.text(&format!("Image: {}.", alt));
+                        }
+                    }
+                    "br" => writer.soft_break(),
+                    "hr" => writer.paragraph_break(),
+                    "blockquote" => {
+                        writer.paragraph_break();
+                        writer.text("Quote:");
+                    }
+                    block if BLOCK_ELEMENTS.contains(&block) => writer.paragraph_break(),
+                    _ => {}
+                }
+            }
+            Token::End(name) => match name.as_str() {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    finish_heading(&mut writer, &mut heading_start);
+                }
+                "ul" | "ol" | "menu" => {
+                    if let Some(list) = lists.pop() {
+                        let plural = if list.items == 1 { "item" } else { "items" };
+                        writer.insert(list.start, &format!("List of {} {}:\n", list.items, plural));
+                    }
+                    writer.paragraph_break();
+                }
+                "li" | "tr" => writer.line_break(),
+                "table" => {
+                    if let Some(table) = tables.pop() {
+                        let intro = describe_table(table.rows, table.columns);
+                        writer.insert(table.start, &format!("{}\n", intro));
+                    }
+                    writer.paragraph_break();
+                }
+                block if BLOCK_ELEMENTS.contains(&block) => writer.paragraph_break(),
+                _ => {}
+            },
+            Token::Text(text) => writer.inline(&text),
+        }
+    }
+
+    finish_heading(&mut writer, &mut heading_start);
+    writer.finish()
+}
+
+/// Announce a heading whose text has been written since `heading_start`
+fn finish_heading(writer: &mut SpeechWriter, heading_start: &mut Option<usize>) {
+    if let Some(start) = heading_start.take() {
+        let title = writer.take_from(start);
+        writer.heading(&title);
+    }
+}
+
+/// Read, summarize or skip a `<pre>` block
+fn read_preformatted(writer: &mut SpeechWriter, code: &str, mode: CodeBlockMode) {
+    match mode {
+        CodeBlockMode::Skip => {}
+        CodeBlockMode::Summarize => {
+            writer.paragraph_break();
+            writer.text(&summarize_code("", code, "block"));
+            writer.paragraph_break();
+        }
+        CodeBlockMode::Read => {
+            writer.paragraph_break();
+            for line in code.lines().filter(|line| !line.trim().is_empty()) {
+                writer.text(line.trim());
+                writer.line_break();
+            }
+            writer.paragraph_break();
+        }
+    }
+}
+
+/// Split HTML into start tags, end tags and decoded text
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+        if open > 0 {
+            tokens.push(Token::Text(decode_entities(&rest[..open])));
+        }
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let is_end = rest.starts_with("</");
+        let name_start = if is_end { 2 } else { 1 };
+        let name_length = rest[name_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == ':'))
+            .unwrap_or(rest.len() - name_start);
+        if name_length == 0 {
+            // A lone '<' is text
+            tokens.push(Token::Text("<".to_string()));
+            rest = &rest[1..];
+            continue;
+        }
+
+        let name = rest[name_start..name_start + name_length].to_ascii_lowercase();
+        let Some(tag_end) = find_tag_end(rest) else {
+            break;
+        };
+        let inside = &rest[name_start + name_length..tag_end];
+        rest = &rest[tag_end + 1..];
+
+        if is_end {
+            tokens.push(Token::End(name));
+            continue;
+        }
+
+        let self_closing = inside.trim_end().ends_with('/');
+        tokens.push(Token::Start {
+            name: name.clone(),
+            attributes: parse_attributes(inside.trim_end().trim_end_matches('/')),
+        });
+
+        // Script and style content is raw text that may contain '<'
+        if matches!(name.as_str(), "script" | "style" | "textarea" | "title") && !self_closing {
+            let closing = format!("</{}", name);
+            let end = rest.to_ascii_lowercase().find(&closing);
+            let (content, remaining) = rest.split_at(end.unwrap_or(rest.len()));
+            if name == "textarea" || name == "title" {
+                tokens.push(Token::Text(decode_entities(content)));
+            }
+            rest = remaining;
+        } else if self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+            tokens.push(Token::End(name));
+        }
+    }
+
+    tokens
+}
+
+/// Find the '>' that closes a tag, ignoring any inside quoted attribute values
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse `name="value"` pairs from the inside of a start tag
+fn parse_attributes(inside: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = inside.trim_start();
+
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+            let (raw, remaining) = match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after_equals[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after_equals
+                        .find(char::is_whitespace)
+                        .unwrap_or(after_equals.len());
+                    after_equals.split_at(end)
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining.trim_start();
+        }
+
+        if !name.is_empty() {
+            attributes.push((name, value));
+        } else if !rest.is_empty() {
+            // Skip a stray character that cannot start a name
+            rest = &rest[rest.chars().next().map_or(1, char::len_utf8)..];
+        }
+    }
+
+    attributes
+}
+
+/// Replace character references such as `&amp;`, `&#8217;` and `&#x2014;`
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        result.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let end = rest[1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+            .map(|end| end + 1)
+            .unwrap_or(rest.len());
+        let entity = &rest[1..end];
+        let decoded = if let Some(number) = entity.strip_prefix("#x").or(entity.strip_prefix("#X"))
+        {
+            u32::from_str_radix(number, 16)
+                .ok()
+                .and_then(char::from_u32)
+        } else if let Some(number) = entity.strip_prefix('#') {
+            number.parse().ok().and_then(char::from_u32)
+        } else {
+            named_entity(entity)
+        };
+
+        match decoded {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// The named character references that show up in ordinary prose
+fn named_entity(name: &str) -> Option<char> {
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        "divide" => '÷',
+        "plusmn" => '±',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "sect" => '§',
+        "para" => '¶',
+        "larr" => '←',
+        "rarr" => '→',
+        "uarr" => '↑',
+        "darr" => '↓',
+        "aacute" => 'á',
+        "eacute" => 'é',
+        "iacute" => 'í',
+        "oacute" => 'ó',
+        "uacute" => 'ú',
+        "agrave" => 'à',
+        "egrave" => 'è',
+        "auml" => 'ä',
+        "ouml" => 'ö',
+        "uuml" => 'ü',
+        "szlig" => 'ß',
+        "ccedil" => 'ç',
+        "ntilde" => 'ñ',
+        _ => return None,
+    };
+    Some(c)
+}
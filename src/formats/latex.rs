@@ -0,0 +1,675 @@
+//! LaTeX source reading: the document body without markup, with sectioning
+//! read as headings and math read as spoken English
+
+use super::markdown::read_code_block;
+use super::{ListState, ReadOptions, SpeechWriter, describe_table, math};
+
+/// Sectioning commands, read as headings
+const SECTIONS: &[&str] = &[
+    "part",
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// Commands whose arguments are layout or metadata, not text
+const DROPPED_COMMANDS: &[(&str, usize)] = &[
+    ("label", 1),
+    ("includegraphics", 1),
+    ("vspace", 1),
+    ("hspace", 1),
+    ("setlength", 2),
+    ("addtolength", 2),
+    ("setcounter", 2),
+    ("addcontentsline", 3),
+    ("newcommand", 2),
+    ("renewcommand", 2),
+    ("newenvironment", 3),
+    ("usepackage", 1),
+    ("documentclass", 1),
+    ("bibliographystyle", 1),
+    ("bibliography", 1),
+    ("index", 1),
+    ("thispagestyle", 1),
+    ("pagestyle", 1),
+    ("color", 1),
+    ("input", 1),
+    ("include", 1),
+];
+
+/// Environments holding math read as display equations
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation",
+    "align",
+    "gather",
+    "multline",
+    "eqnarray",
+    "displaymath",
+    "math",
+    "flalign",
+];
+
+/// Environments left out entirely
+const SKIPPED_ENVIRONMENTS: &[&str] = &["tikzpicture", "comment", "filecontents", "titlepage"];
+
+struct Converter<'a> {
+    writer: SpeechWriter,
+    options: &'a ReadOptions,
+    lists: Vec<ListState>,
+    title: Option<String>,
+    author: Option<String>,
+}
+
+/// Convert LaTeX source into text ready for speech
+pub fn to_speech(source: &str, options: &ReadOptions) -> String {
+    let source = strip_comments(source);
+    let (preamble, body) = match source.split_once("\\begin{document}") {
+        Some((preamble, rest)) => (
+            preamble,
+            rest.split("\\end{document}").next().unwrap_or_default(),
+        ),
+        None => ("", source.as_str()),
+    };
+
+    let mut converter = Converter {
+        writer: SpeechWriter::new(),
+        options,
+        lists: Vec::new(),
+        title: command_argument(preamble, "title"),
+        author: command_argument(preamble, "author"),
+    };
+    converter.convert(body);
+    converter.writer.finish()
+}
+
+impl Converter<'_> {
+    /// Read a run of LaTeX into the writer
+    fn convert(&mut self, source: &str) {
+        let chars: Vec<char> = source.chars().collect();
+        let mut reader = Reader {
+            chars: &chars,
+            position: 0,
+        };
+        let mut text = String::new();
+
+        while let Some(c) = reader.next() {
+            match c {
+                '\\' => {
+                    let name = reader.command_name();
+                    if let Some(symbol) = escaped_symbol(&name) {
+                        text.push_str(symbol);
+                        continue;
+                    }
+                    if let Some(accented) = accent(&name, &mut reader) {
+                        text.push(accented);
+                        continue;
+                    }
+                    self.flush(&mut text);
+                    self.command(&name, &mut reader);
+                }
+                '$' => {
+                    self.flush(&mut text);
+                    if reader.peek() == Some('$') {
+                        reader.next();
+                        let formula = reader.until("$$");
+                        self.display_math(&formula);
+                    } else {
+                        let formula = reader.until("$");
+                        self.inline_math(&formula);
+                    }
+                }
+                '{' | '}' => {}
+                '~' => text.push(' '),
+                '\n' => {
+                    // A blank line ends the paragraph
+                    let rest = reader.rest();
+                    let blank = rest
+                        .iter()
+                        .take_while(|c| c.is_whitespace())
+                        .any(|&c| c == '\n');
+                    if blank {
+                        self.flush(&mut text);
+                        self.writer.paragraph_break();
+                    } else {
+                        text.push(' ');
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        self.flush(&mut text);
+    }
+
+    /// Write pending plain text, turning TeX quotes and dashes into characters
+    fn flush(&mut self, text: &mut String) {
+        if text.is_empty() {
+            return;
+        }
+        let cleaned = text
+            .replace("---", " — ")
+            .replace("--", "–")
+            .replace("``", "\"")
+            .replace("''", "\"");
+        self.writer.inline(&cleaned);
+        text.clear();
+    }
+
+    fn command(&mut self, name: &str, reader: &mut Reader) {
+        let name = name.trim_end_matches('*');
+        if SECTIONS.contains(&name) {
+            reader.skip_star();
+            reader.optional();
+            let title = reader.group().unwrap_or_default();
+            let title = self.to_text(&title);
+            self.writer.heading(&title);
+            return;
+        }
+        if let Some(&(_, count)) = DROPPED_COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+        {
+            reader.skip_star();
+            reader.optional();
+            for _ in 0..count {
+                reader.group();
+            }
+            return;
+        }
+
+        match name {
+            "begin" => {
+                let environment = reader.group().unwrap_or_default();
+                let content = reader.environment_body(&environment);
+                self.environment(&environment, &content);
+            }
+            "(" => {
+                let formula = reader.until("\\)");
+                self.inline_math(&formula);
+            }
+            "[" => {
+                let formula = reader.until("\\]");
+                self.display_math(&formula);
+            }
+            "maketitle" => {
+                if let Some(title) = self.title.clone() {
+                    let title = self.to_text(&title);
+                    self.writer.heading(&title);
+                }
+                if let Some(author) = self.author.clone() {
+                    let author = self.to_text(&author.replace("\\and", " and "));
+                    self.writer.text(&format!("By {}", author));
+                    self.writer.paragraph_break();
+                }
+            }
+            "item" => {
+                self.writer.line_break();
+                let label = reader.optional().map(|label| self.to_text(&label));
+                if let Some(list) = self.lists.last_mut() {
+                    list.items += 1;
+                    if let Some(label) = label {
+                        self.writer.text(&format!("{}:", label.trim()));
+                    } else if let Some(number) = list.item_number() {
+                        self.writer.text(&format!("Item {}:", number));
+                    }
+                }
+            }
+            "footnote" => {
+                let note = reader.group().unwrap_or_default();
+                let note = self.to_text(&note);
+                self.writer.text(&format!("(Footnote: {})", note.trim()));
+            }
+            "caption" => {
+                reader.optional();
+                let caption = reader.group().unwrap_or_default();
+                let caption = self.to_text(&caption);
+                self.writer.paragraph_break();
+                self.writer.text(&format!("Caption: {}", caption));
+                self.writer.paragraph_break();
+            }
+            "ref" | "eqref" | "pageref" | "autoref" | "cref" | "Cref" => {
+                let label = reader.group().unwrap_or_default();
+                self.writer.text(&speakable_label(&label));
+            }
+            "cite" | "citep" | "citet" | "parencite" | "textcite" => {
+                reader.optional();
+                reader.optional();
+                reader.group();
+                self.writer.text("(citation)");
+            }
+            "url" => {
+                reader.group();
+                self.writer.text("link");
+            }
+            "href" => {
+                reader.group();
+                let text = reader.group().unwrap_or_default();
+                self.convert(&text);
+            }
+            "\\" | "newline" | "linebreak" => {
+                reader.skip_star();
+                reader.optional();
+                self.writer.soft_break();
+            }
+            "par" => self.writer.paragraph_break(),
+            // Formatting commands such as \emph{...} and \textbf{...} are read
+            // through their argument, which the main loop reaches next
+            _ => {}
+        }
+    }
+
+    fn environment(&mut self, name: &str, content: &str) {
+        let base = name.trim_end_matches('*');
+        if MATH_ENVIRONMENTS.contains(&base) {
+            for row in content.split("\\\\") {
+                self.display_math(row);
+            }
+            return;
+        }
+        if SKIPPED_ENVIRONMENTS.contains(&base) {
+            return;
+        }
+
+        match base {
+            "itemize" | "enumerate" | "description" => {
+                self.writer.paragraph_break();
+                self.lists.push(ListState {
+                    first_number: (base == "enumerate").then_some(1),
+                    items: 0,
+                    start: self.writer.len(),
+                });
+                self.convert(content);
+                if let Some(list) = self.lists.pop() {
+                    let plural = if list.items == 1 { "item" } else { "items" };
+                    self.writer
+                        .insert(list.start, &format!("List of {} {}:\n", list.items, plural));
+                }
+                self.writer.paragraph_break();
+            }
+            "tabular" | "tabularx" | "longtable" => self.table(content),
+            "verbatim" | "lstlisting" | "minted" | "Verbatim" => {
+                let mut code = content;
+                let mut language = String::new();
+                if let Some(rest) = code.strip_prefix('[') {
+                    let (settings, rest) = rest.split_once(']').unwrap_or_default();
+                    language = settings
+                        .split(',')
+                        .find_map(|setting| setting.trim().strip_prefix("language="))
+                        .unwrap_or_default()
+                        .to_string();
+                    code = rest;
+                }
+                if base == "minted"
+                    && let Some(rest) = code.strip_prefix('{')
+                {
+                    let (name, rest) = rest.split_once('}').unwrap_or_default();
+                    language = name.to_string();
+                    code = rest;
+                }
+                read_code_block(
+                    &mut self.writer,
+                    &language,
+                    code,
+                    "block",
+                    self.options.code_blocks,
+                );
+            }
+            "abstract" => {
+                self.writer.heading("Abstract");
+                self.convert(content);
+                self.writer.paragraph_break();
+            }
+            "quote" | "quotation" => {
+                self.writer.paragraph_break();
+                self.writer.text("Quote:");
+                self.convert(content);
+                self.writer.paragraph_break();
+            }
+            "thebibliography" => {
+                self.writer.heading("References");
+                let content = content.split_once('}').map_or(content, |(_, rest)| rest);
+                for entry in content.split("\\bibitem").skip(1) {
+                    let entry = entry.trim_start();
+                    let entry = entry
+                        .strip_prefix('[')
+                        .and_then(|rest| rest.split_once(']'))
+                        .map_or(entry, |(_, rest)| rest);
+                    let entry = entry
+                        .trim_start()
+                        .strip_prefix('{')
+                        .and_then(|rest| rest.split_once('}'))
+                        .map_or(entry, |(_, rest)| rest);
+                    self.writer.line_break();
+                    self.convert(entry);
+                }
+                self.writer.paragraph_break();
+            }
+            "figure" | "table" => {
+                // Only the caption of a float is read; the main loop handles it
+                let content = content
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.split_once(']'))
+                    .map_or(content, |(_, rest)| rest);
+                self.convert(content);
+            }
+            _ => self.convert(content),
+        }
+    }
+
+    /// Rows separated by `\\`, cells by `&`
+    fn table(&mut self, content: &str) {
+        // The column specification comes first
+        let content = content
+            .trim_start()
+            .strip_prefix('{')
+            .map_or(content, |rest| skip_group(rest));
+        let rows: Vec<Vec<String>> = content
+            .split("\\\\")
+            .map(|row| {
+                row.replace("\\hline", "")
+                    .replace("\\toprule", "")
+                    .replace("\\midrule", "")
+                    .replace("\\bottomrule", "")
+                    .split('&')
+                    .map(|cell| self.to_text(cell).trim().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+            .collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        self.writer.paragraph_break();
+        self.writer.text(&describe_table(rows.len(), columns));
+        self.writer.line_break();
+        for row in rows {
+            for (index, cell) in row.iter().enumerate() {
+                if index > 0 {
+                    self.writer.separator();
+                }
+                self.writer.inline(cell);
+            }
+            self.writer.line_break();
+        }
+        self.writer.paragraph_break();
+    }
+
+    fn inline_math(&mut self, formula: &str) {
+        let spoken = math::verbalize(formula);
+        self.writer.text(&spoken);
+    }
+
+    fn display_math(&mut self, formula: &str) {
+        let spoken = math::verbalize(formula);
+        if spoken.is_empty() {
+            return;
+        }
+        self.writer.paragraph_break();
+        self.writer.text(&format!("Equation: {}", spoken));
+        self.writer.paragraph_break();
+    }
+
+    /// Convert a fragment on its own, for headings, captions and table cells
+    fn to_text(&self, source: &str) -> String {
+        let mut converter = Converter {
+            writer: SpeechWriter::new(),
+            options: self.options,
+            lists: Vec::new(),
+            title: None,
+            author: None,
+        };
+        converter.convert(source);
+        let text = converter.writer.finish();
+        text.strip_suffix('.').unwrap_or(&text).to_string()
+    }
+}
+
+/// A cursor over LaTeX source
+struct Reader<'a> {
+    chars: &'a [char],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.position).copied();
+        self.position += 1;
+        c
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn rest(&self) -> &[char] {
+        self.chars.get(self.position..).unwrap_or_default()
+    }
+
+    /// A command name after a backslash: letters, or a single other character
+    fn command_name(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.position += 1;
+        }
+        if self.position == start && self.peek().is_some() {
+            self.position += 1;
+        }
+        let name: String = self.chars[start..self.position.min(self.chars.len())]
+            .iter()
+            .collect();
+        // Spaces after a command word are not part of the text
+        if name.chars().all(|c| c.is_ascii_alphabetic()) {
+            while self.peek() == Some(' ') {
+                self.position += 1;
+            }
+        }
+        name
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn skip_star(&mut self) {
+        if self.peek() == Some('*') {
+            self.position += 1;
+        }
+    }
+
+    /// A `{...}` argument with nested braces, without the outer braces
+    fn group(&mut self) -> Option<String> {
+        self.delimited('{', '}')
+    }
+
+    /// An optional `[...]` argument
+    fn optional(&mut self) -> Option<String> {
+        self.delimited('[', ']')
+    }
+
+    fn delimited(&mut self, open: char, close: char) -> Option<String> {
+        let saved = self.position;
+        self.skip_whitespace();
+        if self.peek() != Some(open) {
+            self.position = saved;
+            return None;
+        }
+        self.position += 1;
+        let start = self.position;
+        let mut depth = 1;
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => self.position += 1,
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(self.chars[start..self.position - 1].iter().collect());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(self.chars[start..].iter().collect())
+    }
+
+    /// Everything up to `end`, consuming `end`
+    fn until(&mut self, end: &str) -> String {
+        let end: Vec<char> = end.chars().collect();
+        let start = self.position;
+        while self.position < self.chars.len() {
+            if self.chars[self.position] == '\\' && end[0] != '\\' {
+                self.position += 2;
+                continue;
+            }
+            if self.chars[self.position..].starts_with(&end) {
+                let content = self.chars[start..self.position].iter().collect();
+                self.position += end.len();
+                return content;
+            }
+            self.position += 1;
+        }
+        self.chars[start.min(self.chars.len())..].iter().collect()
+    }
+
+    /// The body of an environment up to its matching `\end`
+    fn environment_body(&mut self, name: &str) -> String {
+        let begin: Vec<char> = format!("\\begin{{{}}}", name).chars().collect();
+        let end: Vec<char> = format!("\\end{{{}}}", name).chars().collect();
+        let start = self.position;
+        let mut depth = 1;
+        while self.position < self.chars.len() {
+            let rest = &self.chars[self.position..];
+            if rest.starts_with(&begin) {
+                depth += 1;
+                self.position += begin.len();
+            } else if rest.starts_with(&end) {
+                depth -= 1;
+                if depth == 0 {
+                    let content = self.chars[start..self.position].iter().collect();
+                    self.position += end.len();
+                    return content;
+                }
+                self.position += end.len();
+            } else {
+                self.position += 1;
+            }
+        }
+        self.chars[start.min(self.chars.len())..].iter().collect()
+    }
+}
+
+/// Remove `%` comments, keeping escaped `\%`
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    for line in source.lines() {
+        let mut escaped = false;
+        let mut cut = line.len();
+        for (index, c) in line.char_indices() {
+            match c {
+                '\\' => escaped = !escaped,
+                '%' if !escaped => {
+                    cut = index;
+                    break;
+                }
+                _ => escaped = false,
+            }
+        }
+        let kept = &line[..cut];
+        // A comment ending a line also swallows the line break
+        if cut < line.len() && !kept.trim().is_empty() {
+            out.push_str(kept);
+            continue;
+        }
+        if cut < line.len() {
+            continue;
+        }
+        out.push_str(kept);
+        out.push('\n');
+    }
+    out
+}
+
+/// The argument of the first `\name{...}` in `source`
+fn command_argument(source: &str, name: &str) -> Option<String> {
+    let start = source.find(&format!("\\{}", name))? + name.len() + 1;
+    let chars: Vec<char> = source[start..].chars().collect();
+    let mut reader = Reader {
+        chars: &chars,
+        position: 0,
+    };
+    reader.optional();
+    reader.group()
+}
+
+/// Skip the rest of a `{...}` group whose opening brace was already consumed
+fn skip_group(source: &str) -> &str {
+    let mut depth = 1;
+    for (index, c) in source.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &source[index + 1..];
+                }
+            }
+            _ => {}
+        }
+    }
+    ""
+}
+
+/// Characters written with a backslash, such as `\%` and `\&`
+fn escaped_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "%" => "%",
+        "&" => "&",
+        "$" => "$",
+        "#" => "#",
+        "_" => "_",
+        "{" => "{",
+        "}" => "}",
+        " " | "," | ";" | "quad" | "qquad" => " ",
+        "-" | "/" | "@" => "",
+        "LaTeX" => "LaTeX",
+        "TeX" => "TeX",
+        "ldots" | "dots" | "textellipsis" => "…",
+        _ => return None,
+    })
+}
+
+/// Accented letters such as `\'e` and `\"{o}`
+fn accent(name: &str, reader: &mut Reader) -> Option<char> {
+    const ACCENTED: &[(&str, &str, &str)] = &[
+        ("'", "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+        ("`", "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        ("^", "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        ("\"", "aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+        ("~", "anoANO", "ãñõÃÑÕ"),
+        ("c", "cC", "çÇ"),
+    ];
+    let (_, plain, accented) = ACCENTED.iter().find(|(accent, _, _)| *accent == name)?;
+
+    let saved = reader.position;
+    let letter = match reader.group() {
+        Some(group) => group.chars().next(),
+        None => reader.next(),
+    };
+    let index = letter.and_then(|letter| plain.chars().position(|c| c == letter));
+    match index {
+        Some(index) => accented.chars().nth(index),
+        None => {
+            reader.position = saved;
+            None
+        }
+    }
+}
+
+/// Read a cross-reference label such as `sec:related-work` as "related work"
+fn speakable_label(label: &str) -> String {
+    let name = label.rsplit(':').next().unwrap_or(label);
+    name.replace(['-', '_'], " ")
+}
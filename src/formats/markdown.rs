@@ -0,0 +1,251 @@
+//! Markdown reading: headings, lists, links and code blocks read as speech
+
+use super::{CodeBlockMode, ListState, ReadOptions, SpeechWriter, summarize_code};
+use pulldown_cmark::{
+    CodeBlockKind, Event, LinkType, Options, Parser, Tag, TagEnd, TextMergeStream,
+};
+
+/// Convert Markdown source into text ready for speech
+pub fn to_speech(source: &str, options: &ReadOptions) -> String {
+    let parser_options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut writer = SpeechWriter::new();
+    let mut lists: Vec<ListState> = Vec::new();
+    let mut heading_start = None;
+    let mut image_start = None;
+    let mut code_block: Option<(String, String)> = None;
+    // Autolinks and footnote bodies are not read, so their text is dropped
+    let mut skip_depth = 0;
+
+    // Escapes and entities split text into several events; merge them back so
+    // words are not broken apart
+    for event in TextMergeStream::new(Parser::new_ext(source, parser_options)) {
+        if let Some((_, code)) = &mut code_block {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    let (language, code) = code_block.take().unwrap_or_default();
+                    read_code_block(&mut writer, &language, &code, "block", options.code_blocks);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if skip_depth > 0 {
+            match event {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_) => skip_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Heading { .. } => {
+                    writer.paragraph_break();
+                    heading_start = Some(writer.len());
+                }
+                Tag::Paragraph if lists.is_empty() => writer.paragraph_break(),
+                Tag::BlockQuote(_) => {
+                    writer.paragraph_break();
+                    writer.text("Quote:");
+                }
+                Tag::CodeBlock(kind) => {
+                    let language = match kind {
+                        CodeBlockKind::Fenced(info) => info.to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code_block = Some((language, String::new()));
+                }
+                Tag::List(first_number) => {
+                    if lists.is_empty() {
+                        writer.paragraph_break();
+                    } else {
+                        writer.line_break();
+                    }
+                    lists.push(ListState {
+                        first_number,
+                        items: 0,
+                        start: writer.len(),
+                    });
+                }
+                Tag::Item => {
+                    writer.line_break();
+                    if let Some(list) = lists.last_mut() {
+                        list.items += 1;
+                        if let Some(number) = list.item_number() {
+                            writer.text(&format!("Item {}:", number));
+                        }
+                    }
+                }
+                Tag::Table(_) => {
+                    writer.paragraph_break();
+                    writer.text("Table:");
+                    writer.line_break();
+                }
+                Tag::Link { link_type, .. } => {
+                    // The text of an autolink is the URL itself
+                    if matches!(link_type, LinkType::Autolink | LinkType::Email) {
+                        writer.text("link");
+                        skip_depth = 1;
+                    }
+                }
+                Tag::Image { .. } => image_start = Some(writer.len()),
+                Tag::FootnoteDefinition(_) | Tag::HtmlBlock | Tag::MetadataBlock(_) => {
+                    skip_depth = 1;
+                }
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Heading(_) => {
+                    if let Some(start) = heading_start.take() {
+                        let title = writer.take_from(start);
+                        writer.heading(&title);
+                    }
+                }
+                TagEnd::Paragraph | TagEnd::BlockQuote(_) | TagEnd::Table => {
+                    if lists.is_empty() {
+                        writer.paragraph_break();
+                    } else {
+                        writer.line_break();
+                    }
+                }
+                TagEnd::List(_) => {
+                    if let Some(list) = lists.pop() {
+                        let plural = if list.items == 1 { "item" } else { "items" };
+                        writer.insert(list.start, &format!("List of {} {}:\n", list.items, plural));
+                    }
+                    if lists.is_empty() {
+                        writer.paragraph_break();
+                    }
+                }
+                TagEnd::Item | TagEnd::TableHead | TagEnd::TableRow => writer.line_break(),
+                TagEnd::TableCell => writer.separator(),
+                TagEnd::Image => {
+                    if let Some(start) = image_start.take() {
+                        let alt = writer.take_from(start);
+                        if !alt.trim().is_empty() {
+                            writer.text(&format!("Image: {}.", alt.trim()));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text) | Event::Code(text) => writer.text(&text),
+            Event::InlineMath(math) | Event::DisplayMath(math) => writer.text(&math),
+            Event::SoftBreak => writer.text(" "),
+            Event::HardBreak => writer.line_break(),
+            Event::Rule => writer.paragraph_break(),
+            Event::TaskListMarker(done) => writer.text(if done { "Done:" } else { "To do:" }),
+            Event::Html(_) | Event::InlineHtml(_) | Event::FootnoteReference(_) => {}
+        }
+    }
+
+    writer.finish()
+}
+
+/// Read, summarize or skip a code block
+pub(super) fn read_code_block(
+    writer: &mut SpeechWriter,
+    language: &str,
+    code: &str,
+    kind: &str,
+    mode: CodeBlockMode,
+) {
+    match mode {
+        CodeBlockMode::Skip => {}
+        CodeBlockMode::Summarize => {
+            writer.paragraph_break();
+            writer.text(&summarize_code(language, code, kind));
+            writer.paragraph_break();
+        }
+        CodeBlockMode::Read => {
+            writer.paragraph_break();
+            for line in code.lines().filter(|line| !line.trim().is_empty()) {
+                writer.text(line.trim());
+                writer.line_break();
+            }
+            writer.paragraph_break();
+        }
+    }
+}
+
+/// Escape text so Markdown reads it literally, for readers that convert
+/// other markup to Markdown
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A fenced code block, with a fence longer than any backtick run in the code
+pub(super) fn fence(language: &str, code: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!(
+        "{}{}\n{}\n{}",
+        fence,
+        language,
+        code.trim_end_matches('\n'),
+        fence
+    )
+}
+
+/// A Markdown table from rows of already escaped cells; the first row is the header
+pub(super) fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    if columns == 0 {
+        return String::new();
+    }
+    let line = |cells: &[String]| {
+        let mut cells = cells.to_vec();
+        cells.resize(columns, String::new());
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+/// Common leading whitespace removed from block lines
+pub(super) fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default().trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Add a block on its own lines, indented by `pad` to sit inside a list item
+pub(super) fn push_block(out: &mut Vec<String>, pad: &str, block: &str) {
+    out.push(String::new());
+    for line in block.lines() {
+        if line.is_empty() {
+            out.push(String::new());
+        } else {
+            out.push(format!("{}{}", pad, line));
+        }
+    }
+    out.push(String::new());
+}
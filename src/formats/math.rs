@@ -0,0 +1,521 @@
+//! Spoken English for TeX math, e.g. `\frac{x^2 + 1}{2}` as "x squared plus 1 over 2"
+//!
+//! Covers the common subset: operators and relations, fractions, roots,
+//! powers and subscripts, sums, integrals and limits, Greek letters,
+//! functions and accents. Unknown commands are read by name.
+
+/// One parsed piece of math
+#[derive(Debug, Clone, PartialEq)]
+enum Atom {
+    Command(String),
+    Letter(char),
+    Number(String),
+    Symbol(char),
+    Group(Vec<Atom>),
+}
+
+/// Commands read as a word or phrase
+const WORDS: &[(&str, &str)] = &[
+    // Relations
+    ("le", "less than or equal to"),
+    ("leq", "less than or equal to"),
+    ("ge", "greater than or equal to"),
+    ("geq", "greater than or equal to"),
+    ("ne", "not equal to"),
+    ("neq", "not equal to"),
+    ("approx", "approximately equal to"),
+    ("equiv", "is equivalent to"),
+    ("sim", "similar to"),
+    ("simeq", "similar to"),
+    ("propto", "is proportional to"),
+    ("ll", "much less than"),
+    ("gg", "much greater than"),
+    ("in", "in"),
+    ("notin", "not in"),
+    ("subset", "subset of"),
+    ("subseteq", "subset of or equal to"),
+    ("supset", "superset of"),
+    ("supseteq", "superset of or equal to"),
+    ("to", "goes to"),
+    ("rightarrow", "goes to"),
+    ("mapsto", "maps to"),
+    ("Rightarrow", "implies"),
+    ("implies", "implies"),
+    ("iff", "if and only if"),
+    ("Leftrightarrow", "if and only if"),
+    ("mid", "such that"),
+    // Operators
+    ("times", "times"),
+    ("cdot", "times"),
+    ("div", "divided by"),
+    ("pm", "plus or minus"),
+    ("mp", "minus or plus"),
+    ("cup", "union"),
+    ("cap", "intersection"),
+    ("setminus", "minus"),
+    ("circ", "composed with"),
+    ("oplus", "direct sum"),
+    ("otimes", "tensor"),
+    ("wedge", "and"),
+    ("land", "and"),
+    ("vee", "or"),
+    ("lor", "or"),
+    ("neg", "not"),
+    ("lnot", "not"),
+    // Symbols
+    ("infty", "infinity"),
+    ("partial", "partial"),
+    ("nabla", "nabla"),
+    ("forall", "for all"),
+    ("exists", "there exists"),
+    ("emptyset", "the empty set"),
+    ("varnothing", "the empty set"),
+    ("ldots", "dot dot dot"),
+    ("cdots", "dot dot dot"),
+    ("dots", "dot dot dot"),
+    ("prime", "prime"),
+    ("angle", "angle"),
+    ("degree", "degrees"),
+    ("hbar", "h bar"),
+    ("ell", "l"),
+    // Functions
+    ("sin", "sine"),
+    ("cos", "cosine"),
+    ("tan", "tangent"),
+    ("cot", "cotangent"),
+    ("sec", "secant"),
+    ("csc", "cosecant"),
+    ("arcsin", "arc sine"),
+    ("arccos", "arc cosine"),
+    ("arctan", "arc tangent"),
+    ("sinh", "hyperbolic sine"),
+    ("cosh", "hyperbolic cosine"),
+    ("tanh", "hyperbolic tangent"),
+    ("log", "log"),
+    ("ln", "natural log"),
+    ("exp", "exp"),
+    ("det", "determinant"),
+    ("dim", "dimension"),
+    ("max", "max"),
+    ("min", "min"),
+    ("sup", "supremum"),
+    ("inf", "infimum"),
+    ("gcd", "gcd"),
+    ("Pr", "probability"),
+    // Delimiters
+    ("langle", "open angle bracket"),
+    ("rangle", "close angle bracket"),
+    ("lfloor", "floor of"),
+    ("rfloor", "end floor"),
+    ("lceil", "ceiling of"),
+    ("rceil", "end ceiling"),
+    ("{", "open brace"),
+    ("}", "close brace"),
+    ("|", "norm"),
+    ("%", "percent"),
+];
+
+const GREEK: &[&str] = &[
+    "alpha",
+    "beta",
+    "gamma",
+    "delta",
+    "epsilon",
+    "varepsilon",
+    "zeta",
+    "eta",
+    "theta",
+    "vartheta",
+    "iota",
+    "kappa",
+    "lambda",
+    "mu",
+    "nu",
+    "xi",
+    "pi",
+    "varpi",
+    "rho",
+    "varrho",
+    "sigma",
+    "varsigma",
+    "tau",
+    "upsilon",
+    "phi",
+    "varphi",
+    "chi",
+    "psi",
+    "omega",
+    "Gamma",
+    "Delta",
+    "Theta",
+    "Lambda",
+    "Xi",
+    "Pi",
+    "Sigma",
+    "Upsilon",
+    "Phi",
+    "Psi",
+    "Omega",
+];
+
+/// Big operators read with their limits: "the sum from i equals 1 to n of"
+const BIG_OPERATORS: &[(&str, &str)] = &[
+    ("sum", "the sum"),
+    ("prod", "the product"),
+    ("int", "the integral"),
+    ("iint", "the double integral"),
+    ("iiint", "the triple integral"),
+    ("oint", "the contour integral"),
+    ("bigcup", "the union"),
+    ("bigcap", "the intersection"),
+];
+
+/// Accents read after their argument: `\hat{x}` is "x hat"
+const ACCENTS: &[(&str, &str)] = &[
+    ("hat", "hat"),
+    ("widehat", "hat"),
+    ("bar", "bar"),
+    ("overline", "bar"),
+    ("tilde", "tilde"),
+    ("widetilde", "tilde"),
+    ("dot", "dot"),
+    ("ddot", "double dot"),
+];
+
+/// Commands whose argument is read as plain text
+const TEXT_COMMANDS: &[&str] = &[
+    "text",
+    "textrm",
+    "textit",
+    "textbf",
+    "mathrm",
+    "mathit",
+    "mathbf",
+    "mathsf",
+    "mathtt",
+    "mathcal",
+    "mathbb",
+    "mathfrak",
+    "boldsymbol",
+    "operatorname",
+    "mbox",
+];
+
+/// Spacing, sizing and layout commands that are not read
+const SILENT_COMMANDS: &[&str] = &[
+    ",",
+    ";",
+    ":",
+    "!",
+    " ",
+    "quad",
+    "qquad",
+    "left",
+    "right",
+    "big",
+    "Big",
+    "bigg",
+    "Bigg",
+    "bigl",
+    "bigr",
+    "Bigl",
+    "Bigr",
+    "displaystyle",
+    "textstyle",
+    "limits",
+    "nolimits",
+    "nonumber",
+    "notag",
+    "label",
+    "tag",
+];
+
+/// Read TeX math as spoken English
+pub(crate) fn verbalize(source: &str) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut position = 0;
+    let atoms = parse(&chars, &mut position);
+    tidy(&speak(&atoms))
+}
+
+/// Parse until the end of input or a closing brace
+fn parse(chars: &[char], position: &mut usize) -> Vec<Atom> {
+    let mut atoms = Vec::new();
+    while let Some(&c) = chars.get(*position) {
+        *position += 1;
+        match c {
+            '}' => break,
+            '{' => atoms.push(Atom::Group(parse(chars, position))),
+            '\\' => {
+                let start = *position;
+                while chars
+                    .get(*position)
+                    .is_some_and(|c| c.is_ascii_alphabetic())
+                {
+                    *position += 1;
+                }
+                if *position == start && *position < chars.len() {
+                    *position += 1;
+                }
+                atoms.push(Atom::Command(chars[start..*position].iter().collect()));
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some(&next) = chars.get(*position) {
+                    let decimal_point =
+                        next == '.' && chars.get(*position + 1).is_some_and(|c| c.is_ascii_digit());
+                    if !next.is_ascii_digit() && !decimal_point {
+                        break;
+                    }
+                    number.push(next);
+                    *position += 1;
+                }
+                atoms.push(Atom::Number(number));
+            }
+            c if c.is_whitespace() || c == '&' || c == '~' => {}
+            c if c.is_alphabetic() => atoms.push(Atom::Letter(c)),
+            c => atoms.push(Atom::Symbol(c)),
+        }
+    }
+    atoms
+}
+
+/// Speak a sequence of atoms
+fn speak(atoms: &[Atom]) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut index = 0;
+
+    while index < atoms.len() {
+        let atom = &atoms[index];
+        index += 1;
+        match atom {
+            Atom::Letter(c) => words.push(c.to_string()),
+            Atom::Number(number) => words.push(number.clone()),
+            Atom::Group(inner) => words.push(speak(inner)),
+            Atom::Symbol('^') => {
+                let exponent = take_argument(atoms, &mut index);
+                words.push(power(exponent));
+            }
+            Atom::Symbol('_') => {
+                let subscript = take_argument(atoms, &mut index);
+                words.push(format!("sub {}", speak_one(subscript)));
+            }
+            Atom::Symbol(c) => words.push(symbol(*c).to_string()),
+            Atom::Command(name) => words.push(command(name, atoms, &mut index)),
+        }
+    }
+    words.join(" ")
+}
+
+/// Speak a command, consuming its arguments from `atoms`
+fn command(name: &str, atoms: &[Atom], index: &mut usize) -> String {
+    let name = name.trim_end_matches('*');
+    if SILENT_COMMANDS.contains(&name) {
+        if matches!(name, "label" | "tag") {
+            take_argument(atoms, index);
+        }
+        return String::new();
+    }
+    if let Some((_, word)) = WORDS.iter().find(|(command, _)| *command == name) {
+        return word.to_string();
+    }
+    if GREEK.contains(&name) {
+        return name.trim_start_matches("var").to_string();
+    }
+    if TEXT_COMMANDS.contains(&name) {
+        let argument = take_argument(atoms, index);
+        return plain_text(argument);
+    }
+    if let Some((_, word)) = ACCENTS.iter().find(|(command, _)| *command == name) {
+        let argument = take_argument(atoms, index);
+        return format!("{} {}", speak_one(argument), word);
+    }
+    if name == "vec" {
+        return format!("vector {}", speak_one(take_argument(atoms, index)));
+    }
+    if let Some((_, phrase)) = BIG_OPERATORS.iter().find(|(command, _)| *command == name) {
+        return big_operator(phrase, atoms, index);
+    }
+
+    match name {
+        "frac" | "dfrac" | "tfrac" | "cfrac" => {
+            let numerator = speak_one(take_argument(atoms, index));
+            let denominator = speak_one(take_argument(atoms, index));
+            format!("{} over {},", numerator, denominator)
+        }
+        "binom" | "choose" => {
+            let top = speak_one(take_argument(atoms, index));
+            let bottom = speak_one(take_argument(atoms, index));
+            format!("{} choose {}", top, bottom)
+        }
+        "sqrt" => {
+            let degree = take_optional(atoms, index);
+            let radicand = speak_one(take_argument(atoms, index));
+            match degree.as_deref() {
+                None | Some("2") => format!("the square root of {},", radicand),
+                Some("3") => format!("the cube root of {},", radicand),
+                Some(degree) => format!("the {} root of {},", ordinal(degree), radicand),
+            }
+        }
+        "lim" | "limsup" | "liminf" => {
+            let below = take_limits(atoms, index).0;
+            match below {
+                Some(approach) => format!("the limit as {} of", approach),
+                None => "the limit of".to_string(),
+            }
+        }
+        "\\" | "cr" => ",".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// "the sum from i equals 1 to n of", "the integral of"
+fn big_operator(phrase: &str, atoms: &[Atom], index: &mut usize) -> String {
+    match take_limits(atoms, index) {
+        (Some(from), Some(to)) => format!("{} from {} to {} of", phrase, from, to),
+        (Some(over), None) => format!("{} over {} of", phrase, over),
+        (None, Some(to)) => format!("{} to {} of", phrase, to),
+        (None, None) => format!("{} of", phrase),
+    }
+}
+
+/// Read `_{...}` and `^{...}` following an operator, in either order
+fn take_limits(atoms: &[Atom], index: &mut usize) -> (Option<String>, Option<String>) {
+    let mut below = None;
+    let mut above = None;
+    loop {
+        while matches!(atoms.get(*index), Some(Atom::Command(name)) if name == "limits" || name == "nolimits")
+        {
+            *index += 1;
+        }
+        match atoms.get(*index) {
+            Some(Atom::Symbol('_')) if below.is_none() => {
+                *index += 1;
+                below = Some(speak_one(take_argument(atoms, index)));
+            }
+            Some(Atom::Symbol('^')) if above.is_none() => {
+                *index += 1;
+                above = Some(speak_one(take_argument(atoms, index)));
+            }
+            _ => return (below, above),
+        }
+    }
+}
+
+/// The next atom as an argument; an empty group when there is none
+fn take_argument<'a>(atoms: &'a [Atom], index: &mut usize) -> &'a Atom {
+    static EMPTY: Atom = Atom::Group(Vec::new());
+    match atoms.get(*index) {
+        Some(atom) => {
+            *index += 1;
+            atom
+        }
+        None => &EMPTY,
+    }
+}
+
+/// An optional `[...]` argument, as raw text
+fn take_optional(atoms: &[Atom], index: &mut usize) -> Option<String> {
+    if atoms.get(*index) != Some(&Atom::Symbol('[')) {
+        return None;
+    }
+    let close = atoms[*index..]
+        .iter()
+        .position(|atom| *atom == Atom::Symbol(']'))?;
+    let inside = &atoms[*index + 1..*index + close];
+    *index += close + 1;
+    Some(speak(inside))
+}
+
+fn speak_one(atom: &Atom) -> String {
+    speak(std::slice::from_ref(atom))
+}
+
+/// "squared", "cubed", "prime", "to the power of n"
+fn power(exponent: &Atom) -> String {
+    let inner = match exponent {
+        Atom::Group(inner) if inner.len() == 1 => &inner[0],
+        other => other,
+    };
+    match inner {
+        Atom::Number(n) if n == "2" => "squared".to_string(),
+        Atom::Number(n) if n == "3" => "cubed".to_string(),
+        Atom::Symbol('\'') => "prime".to_string(),
+        Atom::Command(name) if name == "prime" => "prime".to_string(),
+        Atom::Group(inner) if inner.len() > 1 => format!("to the power of {},", speak(inner)),
+        other => format!("to the power of {}", speak_one(other)),
+    }
+}
+
+fn symbol(c: char) -> &'static str {
+    match c {
+        '+' => "plus",
+        '-' => "minus",
+        '=' => "equals",
+        '<' => "less than",
+        '>' => "greater than",
+        '*' => "times",
+        '/' => "over",
+        '!' => "factorial",
+        '\'' => "prime",
+        '(' => "open paren",
+        ')' => "close paren",
+        '[' => "open bracket",
+        ']' => "close bracket",
+        '|' => "bar",
+        ',' => ",",
+        ';' => ";",
+        ':' => "such that",
+        _ => "",
+    }
+}
+
+/// The letters of a text argument, with spaces kept
+fn plain_text(atom: &Atom) -> String {
+    let mut text = String::new();
+    collect_text(atom, &mut text);
+    text
+}
+
+fn collect_text(atom: &Atom, text: &mut String) {
+    match atom {
+        Atom::Letter(c) | Atom::Symbol(c) => text.push(*c),
+        Atom::Number(number) => text.push_str(number),
+        Atom::Command(name) => {
+            text.push(' ');
+            text.push_str(name);
+            text.push(' ');
+        }
+        Atom::Group(inner) => inner.iter().for_each(|atom| collect_text(atom, text)),
+    }
+}
+
+fn ordinal(number: &str) -> String {
+    match number {
+        "n" | "k" | "m" => format!("{}th", number),
+        number => match number.parse::<u32>() {
+            Ok(n) if n % 100 / 10 != 1 && n % 10 == 1 => format!("{}st", n),
+            Ok(n) if n % 100 / 10 != 1 && n % 10 == 2 => format!("{}nd", n),
+            Ok(n) if n % 100 / 10 != 1 && n % 10 == 3 => format!("{}rd", n),
+            _ => format!("{}th", number),
+        },
+    }
+}
+
+/// Collapse spaces and tidy the commas left by pauses
+fn tidy(spoken: &str) -> String {
+    let mut out = String::new();
+    for word in spoken.split_whitespace() {
+        if word == "," {
+            if !out.is_empty() && !out.ends_with(',') {
+                out.push(',');
+            }
+            continue;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    out.trim_end_matches(',').replace(",,", ",").to_string()
+}
@@ -0,0 +1,523 @@
+//! Input formats
+//!
+//! Structured documents are turned into plain text that reads well aloud
+//! instead of having their markup spoken. The format is picked from the file
+//! extension unless it is set explicitly.
+
+pub mod code;
+pub mod delimited;
+pub mod email;
+pub mod epub;
+pub mod feed;
+pub mod html;
+pub mod latex;
+pub mod markdown;
+pub(crate) mod math;
+pub mod notebook;
+pub mod ocr;
+pub mod office;
+pub mod org;
+pub mod pdf;
+pub mod rst;
+pub mod structured;
+pub mod subtitles;
+
+use crate::encoding::{self, Encoding};
+use crate::errors::{TtsError, TtsResult};
+use std::path::{Path, PathBuf};
+
+/// Formats T2V knows how to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    PlainText,
+    Markdown,
+    Html,
+    Epub,
+    Pdf,
+    Docx,
+    Odt,
+    Image,
+    Subtitles,
+    Email,
+    Notebook,
+    Latex,
+    SourceCode(code::Language),
+    Delimited,
+    Structured(structured::Syntax),
+    Feed,
+    Org,
+    Rst,
+}
+
+impl InputFormat {
+    /// Pick a format from a file extension, falling back to plain text
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "md" | "markdown" | "mdown" | "mkd" => InputFormat::Markdown,
+            "html" | "htm" | "xhtml" => InputFormat::Html,
+            "epub" => InputFormat::Epub,
+            "pdf" => InputFormat::Pdf,
+            "docx" => InputFormat::Docx,
+            "odt" => InputFormat::Odt,
+            "png" | "jpg" | "jpeg" | "tif" | "tiff" => InputFormat::Image,
+            "srt" | "vtt" => InputFormat::Subtitles,
+            "eml" | "mbox" => InputFormat::Email,
+            "ipynb" => InputFormat::Notebook,
+            "tex" | "latex" | "ltx" => InputFormat::Latex,
+            "csv" | "tsv" | "tab" => InputFormat::Delimited,
+            "json" => InputFormat::Structured(structured::Syntax::Json),
+            "yaml" | "yml" => InputFormat::Structured(structured::Syntax::Yaml),
+            "toml" => InputFormat::Structured(structured::Syntax::Toml),
+            "rss" | "atom" => InputFormat::Feed,
+            "org" => InputFormat::Org,
+            "rst" | "rest" => InputFormat::Rst,
+            _ => code::Language::from_path(path)
+                .map_or(InputFormat::PlainText, InputFormat::SourceCode),
+        }
+    }
+
+    /// Parse the value given to `--format`
+    pub fn from_name(name: &str) -> TtsResult<Self> {
+        match name.to_lowercase().as_str() {
+            "text" | "txt" | "plain" => Ok(InputFormat::PlainText),
+            "markdown" | "md" => Ok(InputFormat::Markdown),
+            "html" | "htm" => Ok(InputFormat::Html),
+            "epub" => Ok(InputFormat::Epub),
+            "pdf" => Ok(InputFormat::Pdf),
+            "docx" => Ok(InputFormat::Docx),
+            "odt" => Ok(InputFormat::Odt),
+            "image" => Ok(InputFormat::Image),
+            "subtitles" | "srt" | "vtt" => Ok(InputFormat::Subtitles),
+            "email" | "eml" | "mbox" => Ok(InputFormat::Email),
+            "notebook" | "ipynb" => Ok(InputFormat::Notebook),
+            "latex" | "tex" => Ok(InputFormat::Latex),
+            "csv" | "tsv" => Ok(InputFormat::Delimited),
+            "json" => Ok(InputFormat::Structured(structured::Syntax::Json)),
+            "yaml" | "yml" => Ok(InputFormat::Structured(structured::Syntax::Yaml)),
+            "toml" => Ok(InputFormat::Structured(structured::Syntax::Toml)),
+            "feed" | "rss" | "atom" => Ok(InputFormat::Feed),
+            "org" => Ok(InputFormat::Org),
+            "rst" | "restructuredtext" => Ok(InputFormat::Rst),
+            "code" | "source" => Ok(InputFormat::SourceCode(code::Language::Generic)),
+            other => Err(TtsError::ConfigError(format!(
+                "Unknown input format '{}' (expected text, markdown, html, epub, pdf, docx, odt, image, subtitles, email, notebook, latex, csv, json, yaml, toml, feed, org, rst or code)",
+                other
+            ))),
+        }
+    }
+
+    /// Human-readable name for stats output
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputFormat::PlainText => "plain text",
+            InputFormat::Markdown => "Markdown",
+            InputFormat::Html => "HTML",
+            InputFormat::Epub => "EPUB",
+            InputFormat::Pdf => "PDF",
+            InputFormat::Docx => "Word document",
+            InputFormat::Odt => "OpenDocument text",
+            InputFormat::Image => "image (OCR)",
+            InputFormat::Subtitles => "subtitles",
+            InputFormat::Email => "email",
+            InputFormat::Notebook => "Jupyter notebook",
+            InputFormat::Latex => "LaTeX",
+            InputFormat::SourceCode(_) => "source code",
+            InputFormat::Delimited => "delimited table",
+            InputFormat::Structured(syntax) => syntax.name(),
+            InputFormat::Feed => "news feed",
+            InputFormat::Org => "Org-mode",
+            InputFormat::Rst => "reStructuredText",
+        }
+    }
+}
+
+/// How fenced and indented code blocks are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodeBlockMode {
+    /// Leave code blocks out entirely
+    Skip,
+    /// Say that a code block is there and how long it is
+    #[default]
+    Summarize,
+    /// Read the code as written
+    Read,
+}
+
+impl CodeBlockMode {
+    /// Parse the value given to `--code-blocks`
+    pub fn parse(value: &str) -> TtsResult<Self> {
+        match value {
+            "skip" => Ok(CodeBlockMode::Skip),
+            "summarize" => Ok(CodeBlockMode::Summarize),
+            "read" => Ok(CodeBlockMode::Read),
+            other => Err(TtsError::ConfigError(format!(
+                "Unknown code block mode '{}' (expected skip, summarize or read)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options that control how input files are read
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Character encoding for text formats; detected when `None`
+    pub encoding: Option<&'static Encoding>,
+    pub code_blocks: CodeBlockMode,
+    /// Only read this chapter of a book (1-based)
+    pub chapter: Option<usize>,
+    /// Only read these pages of a PDF
+    pub pages: Option<NumberRange>,
+    /// Only read these data rows of a CSV table
+    pub rows: Option<NumberRange>,
+    /// Only read these CSV columns, by header name or 1-based number
+    pub columns: Vec<String>,
+    /// Only read the values under this key path of a JSON, YAML or TOML document
+    pub select: Option<String>,
+    /// Only read this many of the newest feed items
+    pub latest: Option<usize>,
+    /// File listing the ids of feed items already heard, which are skipped
+    pub heard: Option<PathBuf>,
+    /// Tesseract language codes for OCR, e.g. `eng` or `eng+deu`
+    pub ocr_language: Option<String>,
+    /// Read the text outputs of notebook code cells
+    pub read_outputs: bool,
+    /// Leave out Org-mode TODO keywords, priorities, tags and planning lines
+    pub skip_todo: bool,
+    /// Read Org-mode drawers such as `:PROPERTIES:`
+    pub read_drawers: bool,
+    /// What to read from source code files
+    pub source_mode: code::SourceMode,
+}
+
+/// An inclusive range of pages or rows, as given to `--pages` and `--rows`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberRange {
+    pub first: u32,
+    /// Last page, or `None` for the end of the document
+    pub last: Option<u32>,
+}
+
+impl NumberRange {
+    /// Parse `5`, `3-10` or `3-`
+    pub fn parse(value: &str) -> TtsResult<Self> {
+        let invalid = || {
+            TtsError::ConfigError(format!(
+                "Invalid range '{}' (expected e.g. 5, 3-10 or 3-)",
+                value
+            ))
+        };
+        let number = |text: &str| text.trim().parse::<u32>().ok().filter(|&n| n > 0);
+
+        let range = match value.split_once('-') {
+            None => {
+                let page = number(value).ok_or_else(invalid)?;
+                NumberRange {
+                    first: page,
+                    last: Some(page),
+                }
+            }
+            Some((first, last)) => NumberRange {
+                first: number(first).ok_or_else(invalid)?,
+                last: if last.trim().is_empty() {
+                    None
+                } else {
+                    Some(number(last).ok_or_else(invalid)?)
+                },
+            },
+        };
+
+        if range.last.is_some_and(|last| last < range.first) {
+            return Err(invalid());
+        }
+        Ok(range)
+    }
+
+    pub fn contains(&self, page: u32) -> bool {
+        page >= self.first && self.last.is_none_or(|last| page <= last)
+    }
+}
+
+/// A loaded input, ready for speech
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub text: String,
+    /// Encoding the source was decoded with, for text formats
+    pub encoding: Option<&'static Encoding>,
+    /// Details about how the input was read, for display
+    pub stats: Vec<String>,
+    /// Ids of the feed items read, to add to the heard file once spoken
+    pub item_ids: Vec<String>,
+}
+
+/// Load raw input bytes in the given format into text ready for speech
+pub fn load(format: InputFormat, bytes: &[u8], options: &ReadOptions) -> TtsResult<Document> {
+    let text = match format {
+        InputFormat::Epub => epub::to_speech(bytes, options)?,
+        InputFormat::Docx => office::docx_to_speech(bytes)?,
+        InputFormat::Odt => office::odt_to_speech(bytes)?,
+        InputFormat::Pdf => return pdf::to_speech(bytes, options),
+        InputFormat::Image => return ocr::image_to_speech(bytes, options),
+        InputFormat::Email => return email::to_speech(bytes, options),
+        _ => return load_text(format, bytes, options),
+    };
+    Ok(Document {
+        text,
+        encoding: None,
+        stats: Vec::new(),
+        item_ids: Vec::new(),
+    })
+}
+
+/// Decode a text format and convert its markup
+fn load_text(format: InputFormat, bytes: &[u8], options: &ReadOptions) -> TtsResult<Document> {
+    let decoded = encoding::decode(bytes, options.encoding);
+    let mut stats = vec![format!(
+        "Encoding: {} ({})",
+        decoded.encoding.name(),
+        decoded.source
+    )];
+    if decoded.had_errors {
+        stats.push("Some bytes could not be decoded and were replaced".to_string());
+    }
+
+    let mut item_ids = Vec::new();
+    let text = match format {
+        InputFormat::Markdown => markdown::to_speech(&decoded.text, options),
+        InputFormat::Html => html::to_speech(&decoded.text, options),
+        InputFormat::Subtitles => subtitles::to_speech(&decoded.text)?,
+        InputFormat::Notebook => notebook::to_speech(&decoded.text, options)?,
+        InputFormat::Latex => latex::to_speech(&decoded.text, options),
+        InputFormat::Org => org::to_speech(&decoded.text, options),
+        InputFormat::Rst => rst::to_speech(&decoded.text, options),
+        InputFormat::Delimited => {
+            let (text, mut details) = delimited::to_speech(&decoded.text, options)?;
+            stats.append(&mut details);
+            text
+        }
+        InputFormat::Structured(syntax) => {
+            let (text, mut details) = structured::to_speech(&decoded.text, syntax, options)?;
+            stats.append(&mut details);
+            text
+        }
+        InputFormat::Feed => {
+            let mut feed = feed::to_speech(&decoded.text, options)?;
+            stats.append(&mut feed.stats);
+            item_ids = feed.item_ids;
+            feed.text
+        }
+        InputFormat::SourceCode(language) => {
+            code::to_speech(&decoded.text, language, options.source_mode)
+        }
+        _ => decoded.text,
+    };
+    Ok(Document {
+        text,
+        encoding: Some(decoded.encoding),
+        stats,
+        item_ids,
+    })
+}
+
+/// Describe code for summarized reading, e.g. "Rust code block, 12 lines."
+///
+/// `kind` names what holds the code, such as "block" or "cell".
+pub(crate) fn summarize_code(language: &str, code: &str, kind: &str) -> String {
+    let lines = code.lines().filter(|line| !line.trim().is_empty()).count();
+    let plural = if lines == 1 { "line" } else { "lines" };
+    let language = language.split([',', ' ']).next().unwrap_or_default().trim();
+
+    if language.is_empty() {
+        format!("Code {}, {} {}.", kind, lines, plural)
+    } else {
+        format!("{} code {}, {} {}.", language, kind, lines, plural)
+    }
+}
+
+/// Introduce a table, e.g. "Table with 3 rows and 2 columns:"
+pub(crate) fn describe_table(rows: usize, columns: usize) -> String {
+    format!(
+        "Table with {} {} and {} {}:",
+        rows,
+        if rows == 1 { "row" } else { "rows" },
+        columns,
+        if columns == 1 { "column" } else { "columns" }
+    )
+}
+
+/// A list being read, so its items can be counted and numbered
+pub(crate) struct ListState {
+    /// Number of the first item; `None` for lists read without numbers
+    pub first_number: Option<u64>,
+    pub items: usize,
+    /// Where the list starts in the [`SpeechWriter`] output
+    pub start: usize,
+}
+
+impl ListState {
+    /// Number of the item counted last, if the list is numbered
+    pub fn item_number(&self) -> Option<u64> {
+        self.first_number
+            .map(|first| first + self.items.saturating_sub(1) as u64)
+    }
+}
+
+/// Builds speech text out of blocks, lines and inline runs
+///
+/// Every block ends as a sentence so the engine pauses between blocks, and
+/// blank lines separate paragraphs.
+#[derive(Debug, Default)]
+pub(crate) struct SpeechWriter {
+    out: String,
+}
+
+impl SpeechWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append inline text, inserting a space between words where needed
+    pub fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let needs_space = self
+            .out
+            .chars()
+            .last()
+            .is_some_and(|last| !last.is_whitespace() && !"([\"'".contains(last))
+            && !text.starts_with(|c: char| c.is_whitespace() || ",.;:!?)".contains(c));
+        if needs_space {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+
+    /// Append text exactly where it falls, for sources that carry their own spacing
+    ///
+    /// Whitespace runs collapse to one space, and no space is left at the
+    /// start of a line.
+    pub fn inline(&mut self, text: &str) {
+        // Labels such as "Item 1:" and cell separators come without a trailing space
+        let mut pending_space = self.out.ends_with([':', ',']);
+        for c in text.chars() {
+            if c.is_whitespace() {
+                pending_space = true;
+                continue;
+            }
+            if pending_space {
+                self.space();
+            }
+            pending_space = false;
+            self.out.push(c);
+        }
+        if pending_space {
+            self.space();
+        }
+    }
+
+    /// Append the output of another reader as its own paragraphs
+    pub fn paragraphs(&mut self, speech: &str) {
+        self.paragraph_break();
+        self.out.push_str(speech.trim());
+        self.paragraph_break();
+    }
+
+    /// Add a single space unless the output already ends with whitespace
+    fn space(&mut self) {
+        if self
+            .out
+            .chars()
+            .last()
+            .is_some_and(|last| !last.is_whitespace())
+        {
+            self.out.push(' ');
+        }
+    }
+
+    /// Finish the current sentence with a period unless it already has punctuation
+    pub fn end_sentence(&mut self) {
+        // Insert after the last word rather than trimming, so saved positions stay valid
+        let end = self.out.trim_end().len();
+        match self.out[..end].chars().last() {
+            Some(',') => self.out.replace_range(end - 1..end, "."),
+            Some(last) if !".!?:;…".contains(last) => self.out.insert(end, '.'),
+            _ => {}
+        }
+    }
+
+    /// Separate items within a sentence, such as table cells, with a comma
+    pub fn separator(&mut self) {
+        let last = self.out.trim_end().chars().last();
+        if last.is_some_and(|last| !",.!?:;".contains(last)) {
+            self.text(",");
+        }
+    }
+
+    /// Start a new line without ending the sentence, like an HTML `<br>`
+    pub fn soft_break(&mut self) {
+        if self
+            .out
+            .chars()
+            .last()
+            .is_some_and(|last| !last.is_whitespace())
+        {
+            self.out.push('\n');
+        }
+    }
+
+    /// End the current sentence and start a new line
+    pub fn line_break(&mut self) {
+        self.end_sentence();
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// End the current sentence and leave a blank line, which engines read as a pause
+    pub fn paragraph_break(&mut self) {
+        self.end_sentence();
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    /// A heading read as its own announced paragraph
+    pub fn heading(&mut self, title: &str) {
+        let title = title.trim();
+        if title.is_empty() {
+            return;
+        }
+        self.paragraph_break();
+        self.text(&format!("Heading: {}", title));
+        self.paragraph_break();
+    }
+
+    /// Current length, used to insert text at an earlier position
+    pub fn len(&self) -> usize {
+        self.out.len()
+    }
+
+    /// Insert text at a position returned by `len`
+    pub fn insert(&mut self, position: usize, text: &str) {
+        self.out.insert_str(position, text);
+    }
+
+    /// Take everything written since `position`, removing it from the output
+    pub fn take_from(&mut self, position: usize) -> String {
+        self.out.split_off(position)
+    }
+
+    pub fn finish(mut self) -> String {
+        self.end_sentence();
+        self.out.trim().to_string()
+    }
+}
@@ -0,0 +1,91 @@
+//! Jupyter notebook reading: Markdown cells as prose, code cells by `--code-blocks`
+
+use super::{ReadOptions, SpeechWriter, markdown};
+use crate::errors::{TtsError, TtsResult};
+use serde_json::Value;
+
+/// Read the cells of a notebook in order
+pub fn to_speech(source: &str, options: &ReadOptions) -> TtsResult<String> {
+    let notebook: Value = serde_json::from_str(source)
+        .map_err(|e| TtsError::FileError(format!("Invalid notebook: {}", e)))?;
+    let cells = notebook["cells"]
+        .as_array()
+        .ok_or_else(|| TtsError::FileError("Invalid notebook: no cells".to_string()))?;
+    let language = notebook["metadata"]["kernelspec"]["language"]
+        .as_str()
+        .or(notebook["metadata"]["language_info"]["name"].as_str())
+        .unwrap_or_default();
+
+    let mut writer = SpeechWriter::new();
+    for cell in cells {
+        let source = joined(&cell["source"]);
+        match cell["cell_type"].as_str() {
+            Some("markdown") => {
+                writer.paragraphs(&markdown::to_speech(&source, options));
+            }
+            Some("code") => {
+                if !source.trim().is_empty() {
+                    markdown::read_code_block(
+                        &mut writer,
+                        language,
+                        &source,
+                        "cell",
+                        options.code_blocks,
+                    );
+                }
+                if options.read_outputs {
+                    read_outputs(&mut writer, &cell["outputs"]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(writer.finish())
+}
+
+/// Read the text of a code cell's outputs; images and other media are only mentioned
+fn read_outputs(writer: &mut SpeechWriter, outputs: &Value) {
+    for output in outputs.as_array().into_iter().flatten() {
+        let text = match output["output_type"].as_str() {
+            Some("stream") => joined(&output["text"]),
+            Some("execute_result" | "display_data") => {
+                let data = &output["data"];
+                if data.get("text/plain").is_some() {
+                    joined(&data["text/plain"])
+                } else if data
+                    .as_object()
+                    .is_some_and(|data| data.keys().any(|kind| kind.starts_with("image/")))
+                {
+                    "an image".to_string()
+                } else {
+                    continue;
+                }
+            }
+            Some("error") => format!(
+                "error, {}: {}",
+                output["ename"].as_str().unwrap_or_default(),
+                output["evalue"].as_str().unwrap_or_default()
+            ),
+            _ => continue,
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        writer.paragraph_break();
+        writer.text("Output:");
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            writer.text(line.trim());
+            writer.line_break();
+        }
+        writer.paragraph_break();
+    }
+}
+
+/// Notebook text fields are either a string or a list of lines
+fn joined(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
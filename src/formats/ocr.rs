@@ -0,0 +1,218 @@
+//! Optical character recognition for images and scanned PDFs
+//!
+//! Text is recognized by the `tesseract` binary; scanned PDF pages are first
+//! rendered to images with `pdftoppm` from Poppler.
+
+use super::{Document, ReadOptions};
+use crate::errors::{TtsError, TtsResult};
+use crate::temp::TempDir;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Resolution scanned PDF pages are rendered at; tesseract works best at 300 DPI
+const RENDER_DPI: &str = "300";
+
+/// Average word confidence below which the text is flagged as unreliable
+const LOW_CONFIDENCE: f32 = 60.0;
+
+/// Text recognized on one or more images
+#[derive(Debug, Default)]
+struct Recognized {
+    text: String,
+    /// Sum of word confidences (0-100) and the number of words
+    confidence_sum: f32,
+    words: usize,
+}
+
+impl Recognized {
+    fn append(&mut self, other: Recognized) {
+        if !self.text.is_empty() && !other.text.is_empty() {
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(&other.text);
+        self.confidence_sum += other.confidence_sum;
+        self.words += other.words;
+    }
+
+    fn into_document(self, mut stats: Vec<String>) -> TtsResult<Document> {
+        if self.words == 0 {
+            return Err(TtsError::FileError(
+                "OCR found no text in the image".to_string(),
+            ));
+        }
+        let confidence = self.confidence_sum / self.words as f32;
+        stats.push(format!(
+            "OCR confidence: {:.0}% over {} words",
+            confidence, self.words
+        ));
+        if confidence < LOW_CONFIDENCE {
+            stats.push("Low OCR confidence; the text may contain mistakes".to_string());
+        }
+        Ok(Document {
+            text: self.text,
+            encoding: None,
+            stats,
+            item_ids: Vec::new(),
+        })
+    }
+}
+
+/// Recognize the text in an image file (PNG, JPEG, TIFF)
+pub fn image_to_speech(bytes: &[u8], options: &ReadOptions) -> TtsResult<Document> {
+    let recognized = recognize(Source::Bytes(bytes), options)?;
+    recognized.into_document(vec![language_stat(options)])
+}
+
+/// Render PDF pages without a text layer and recognize their text
+pub fn pdf_to_speech(
+    bytes: &[u8],
+    first: u32,
+    last: u32,
+    options: &ReadOptions,
+) -> TtsResult<Document> {
+    let workspace = TempDir::create("ocr")?;
+    let pdf_path = workspace.path.join("input.pdf");
+    fs::write(&pdf_path, bytes)?;
+
+    let output = Command::new("pdftoppm")
+        .args(["-r", RENDER_DPI, "-png"])
+        .args(["-f", &first.to_string(), "-l", &last.to_string()])
+        .arg(&pdf_path)
+        .arg(workspace.path.join("page"))
+        .output()
+        .map_err(|e| missing_tool(e, "pdftoppm", "poppler-utils"))?;
+    check_output(&output, "pdftoppm")?;
+
+    // pdftoppm pads page numbers to the same width, so names sort in page order
+    let mut images: Vec<PathBuf> = fs::read_dir(&workspace.path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    images.sort();
+
+    let mut recognized = Recognized::default();
+    for image in &images {
+        recognized.append(recognize(Source::File(image), options)?);
+    }
+    recognized.into_document(vec![
+        "No text layer; pages were read with OCR".to_string(),
+        language_stat(options),
+    ])
+}
+
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    Bytes(&'a [u8]),
+    File(&'a Path),
+}
+
+/// Run tesseract and rebuild the text from its word-level TSV output
+fn recognize(source: Source, options: &ReadOptions) -> TtsResult<Recognized> {
+    let mut command = Command::new("tesseract");
+    match source {
+        Source::Bytes(_) => command.arg("stdin"),
+        Source::File(path) => command.arg(path),
+    };
+    command.arg("stdout");
+    if let Some(language) = &options.ocr_language {
+        command.args(["-l", language]);
+    }
+    command
+        .arg("tsv")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| missing_tool(e, "tesseract", "tesseract-ocr"))?;
+    // Tesseract may exit before reading its input; its stderr says why
+    if let (Source::Bytes(bytes), Some(mut stdin)) = (source, child.stdin.take())
+        && let Err(error) = stdin.write_all(bytes)
+        && error.kind() != ErrorKind::BrokenPipe
+    {
+        return Err(error.into());
+    }
+    let output = child.wait_with_output()?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("Failed loading language") {
+        return Err(TtsError::ConfigError(format!(
+            "OCR language '{}' is not installed for tesseract (see `tesseract --list-langs`)",
+            options.ocr_language.as_deref().unwrap_or("eng")
+        )));
+    }
+    check_output(&output, "tesseract")?;
+
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Join recognized words into lines and paragraphs
+///
+/// Columns: level, page, block, paragraph, line, word, left, top, width,
+/// height, confidence, text. Only word rows (level 5) carry text.
+fn parse_tsv(tsv: &str) -> Recognized {
+    let mut recognized = Recognized::default();
+    let mut current_line = None;
+    let mut current_paragraph = None;
+
+    for row in tsv.lines().skip(1) {
+        let columns: Vec<&str> = row.split('\t').collect();
+        if columns.len() < 12 || columns[0] != "5" {
+            continue;
+        }
+        let word = columns[11].trim();
+        let confidence = columns[10].parse::<f32>().unwrap_or(-1.0);
+        if word.is_empty() || confidence < 0.0 {
+            continue;
+        }
+
+        let paragraph = (columns[1], columns[2], columns[3]);
+        let line = (paragraph, columns[4]);
+        if current_paragraph.is_some() && current_paragraph != Some(paragraph) {
+            recognized.text.push_str("\n\n");
+        } else if current_line.is_some() && current_line != Some(line) {
+            recognized.text.push('\n');
+        } else if !recognized.text.is_empty() {
+            recognized.text.push(' ');
+        }
+        current_paragraph = Some(paragraph);
+        current_line = Some(line);
+
+        recognized.text.push_str(word);
+        recognized.confidence_sum += confidence;
+        recognized.words += 1;
+    }
+    recognized
+}
+
+fn language_stat(options: &ReadOptions) -> String {
+    format!(
+        "OCR language: {}",
+        options.ocr_language.as_deref().unwrap_or("eng (default)")
+    )
+}
+
+fn check_output(output: &Output, tool: &str) -> TtsResult<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(TtsError::SystemError(format!(
+        "{} failed: {}",
+        tool,
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+fn missing_tool(error: std::io::Error, tool: &str, package: &str) -> TtsError {
+    if error.kind() == ErrorKind::NotFound {
+        TtsError::SystemError(format!(
+            "OCR needs the '{}' command; install the {} package",
+            tool, package
+        ))
+    } else {
+        error.into()
+    }
+}
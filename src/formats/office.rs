@@ -0,0 +1,376 @@
+//! Word processor documents: Office Open XML (`.docx`) and OpenDocument (`.odt`)
+//!
+//! Both are ZIP archives holding the document body as XML. Paragraphs,
+//! headings, list items and table cells are read; comments, footnotes,
+//! deleted text and field codes are left out.
+
+use super::{SpeechWriter, describe_table};
+use crate::errors::{TtsError, TtsResult};
+use roxmltree::{Document, Node};
+use std::collections::HashSet;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+const WORD_NAMESPACE: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const TEXT_NAMESPACE: &str = "urn:oasis:names:tc:opendocument:xmlns:text:1.0";
+const TABLE_NAMESPACE: &str = "urn:oasis:names:tc:opendocument:xmlns:table:1.0";
+const OFFICE_NAMESPACE: &str = "urn:oasis:names:tc:opendocument:xmlns:office:1.0";
+const DRAW_NAMESPACE: &str = "urn:oasis:names:tc:opendocument:xmlns:drawing:1.0";
+
+/// Read the body of a `.docx` document
+pub fn docx_to_speech(bytes: &[u8]) -> TtsResult<String> {
+    let mut archive = open_archive(bytes, "DOCX")?;
+    let document = read_entry(&mut archive, "word/document.xml", "DOCX")?
+        .ok_or_else(|| invalid("DOCX", "missing 'word/document.xml'"))?;
+    let styles = read_entry(&mut archive, "word/styles.xml", "DOCX")?;
+
+    let heading_styles = match &styles {
+        Some(styles) => docx_heading_styles(&parse_xml(styles, "DOCX")?),
+        None => HashSet::new(),
+    };
+    let document = parse_xml(&document, "DOCX")?;
+    let body = document
+        .descendants()
+        .find(|node| is_word(node, "body"))
+        .ok_or_else(|| invalid("DOCX", "document has no body"))?;
+
+    let mut reader = DocxReader {
+        writer: SpeechWriter::new(),
+        heading_styles,
+        list_items: 0,
+        list_start: 0,
+    };
+    reader.blocks(body);
+    reader.end_list();
+    Ok(reader.writer.finish())
+}
+
+/// Read the body of an `.odt` document
+pub fn odt_to_speech(bytes: &[u8]) -> TtsResult<String> {
+    let mut archive = open_archive(bytes, "ODT")?;
+    let content = read_entry(&mut archive, "content.xml", "ODT")?
+        .ok_or_else(|| invalid("ODT", "missing 'content.xml'"))?;
+    let document = parse_xml(&content, "ODT")?;
+    let body = document
+        .descendants()
+        .find(|node| {
+            node.tag_name().namespace() == Some(OFFICE_NAMESPACE)
+                && node.tag_name().name() == "text"
+        })
+        .ok_or_else(|| invalid("ODT", "document has no text body"))?;
+
+    let mut writer = SpeechWriter::new();
+    odt_blocks(&mut writer, body);
+    Ok(writer.finish())
+}
+
+/// Ids of heading styles, found by style name ("heading 1", "Title")
+///
+/// Style ids are localized in some editors, but the names are not.
+fn docx_heading_styles(styles: &Document) -> HashSet<String> {
+    styles
+        .descendants()
+        .filter(|node| is_word(node, "style"))
+        .filter_map(|style| {
+            let id = style.attribute((WORD_NAMESPACE, "styleId"))?;
+            let name = style
+                .children()
+                .find(|node| is_word(node, "name"))
+                .and_then(|node| node.attribute((WORD_NAMESPACE, "val")))?
+                .to_lowercase();
+            (name.starts_with("heading") || name == "title").then(|| id.to_string())
+        })
+        .collect()
+}
+
+struct DocxReader {
+    writer: SpeechWriter,
+    heading_styles: HashSet<String>,
+    /// Items in the list being read, zero outside lists
+    list_items: usize,
+    list_start: usize,
+}
+
+impl DocxReader {
+    /// Read block content: paragraphs, tables and content controls
+    fn blocks(&mut self, parent: Node) {
+        for node in parent.children().filter(Node::is_element) {
+            if is_word(&node, "p") {
+                self.paragraph(node);
+            } else if is_word(&node, "tbl") {
+                self.end_list();
+                self.table(node);
+            } else if is_word(&node, "sdt") || is_word(&node, "sdtContent") {
+                self.blocks(node);
+            }
+        }
+    }
+
+    fn paragraph(&mut self, paragraph: Node) {
+        let properties = paragraph.children().find(|node| is_word(node, "pPr"));
+        let style = properties
+            .and_then(|pr| pr.children().find(|node| is_word(node, "pStyle")))
+            .and_then(|node| node.attribute((WORD_NAMESPACE, "val")))
+            .unwrap_or_default();
+        // Outline level 9 is body text
+        let is_heading = self.heading_styles.contains(style)
+            || style.to_lowercase().starts_with("heading")
+            || properties
+                .and_then(|pr| pr.children().find(|node| is_word(node, "outlineLvl")))
+                .and_then(|node| node.attribute((WORD_NAMESPACE, "val")))
+                .is_some_and(|level| level != "9");
+        let is_list_item =
+            properties.is_some_and(|pr| pr.children().any(|node| is_word(&node, "numPr")));
+
+        let mut text = String::new();
+        docx_runs(paragraph, &mut text);
+
+        if is_heading {
+            self.end_list();
+            self.writer.heading(&text);
+        } else if is_list_item {
+            if self.list_items == 0 {
+                self.writer.paragraph_break();
+                self.list_start = self.writer.len();
+            }
+            self.list_items += 1;
+            self.writer.line_break();
+            self.writer.inline(&text);
+            self.writer.line_break();
+        } else if !text.trim().is_empty() {
+            self.end_list();
+            self.writer.paragraph_break();
+            self.writer.inline(&text);
+            self.writer.paragraph_break();
+        }
+    }
+
+    fn table(&mut self, table: Node) {
+        let rows: Vec<Node> = table
+            .children()
+            .filter(|node| is_word(node, "tr"))
+            .collect();
+        let columns = rows
+            .iter()
+            .map(|row| row.children().filter(|node| is_word(node, "tc")).count())
+            .max()
+            .unwrap_or(0);
+
+        self.writer.paragraph_break();
+        self.writer.text(&describe_table(rows.len(), columns));
+        self.writer.line_break();
+        for row in rows {
+            for (index, cell) in row
+                .children()
+                .filter(|node| is_word(node, "tc"))
+                .enumerate()
+            {
+                if index > 0 {
+                    self.writer.separator();
+                }
+                let mut text = String::new();
+                for paragraph in cell.descendants().filter(|node| is_word(node, "p")) {
+                    docx_runs(paragraph, &mut text);
+                    text.push(' ');
+                }
+                self.writer.inline(text.trim());
+            }
+            self.writer.line_break();
+        }
+        self.writer.paragraph_break();
+    }
+
+    /// Close a run of list item paragraphs
+    fn end_list(&mut self) {
+        if self.list_items > 0 {
+            let plural = if self.list_items == 1 {
+                "item"
+            } else {
+                "items"
+            };
+            self.writer.insert(
+                self.list_start,
+                &format!("List of {} {}:\n", self.list_items, plural),
+            );
+            self.list_items = 0;
+            self.writer.paragraph_break();
+        }
+    }
+}
+
+/// Collect the text of a paragraph's runs, skipping deleted text and field codes
+fn docx_runs(node: Node, text: &mut String) {
+    for child in node.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            _ if child.tag_name().namespace() != Some(WORD_NAMESPACE) => {}
+            "t" => text.push_str(child.text().unwrap_or_default()),
+            "tab" => text.push(' '),
+            "br" | "cr" => text.push('\n'),
+            "noBreakHyphen" | "softHyphen" => text.push('-'),
+            // Tracked deletions, field instructions and nested paragraphs inside runs
+            "del" | "delText" | "instrText" | "pPr" | "rPr" | "footnoteReference" => {}
+            _ => docx_runs(child, text),
+        }
+    }
+}
+
+/// Read OpenDocument block content: headings, paragraphs, lists and tables
+fn odt_blocks(writer: &mut SpeechWriter, parent: Node) {
+    for node in parent.children().filter(Node::is_element) {
+        let name = node.tag_name().name();
+        match node.tag_name().namespace() {
+            Some(TEXT_NAMESPACE) => match name {
+                "h" => writer.heading(&odt_text(node)),
+                "p" => {
+                    writer.paragraph_break();
+                    writer.inline(&odt_text(node));
+                    writer.paragraph_break();
+                }
+                "list" => {
+                    writer.paragraph_break();
+                    odt_list(writer, node);
+                    writer.paragraph_break();
+                }
+                "section" => odt_blocks(writer, node),
+                _ => {}
+            },
+            Some(TABLE_NAMESPACE) if name == "table" => odt_table(writer, node),
+            _ => {}
+        }
+    }
+}
+
+/// Read a list and the lists nested in its items
+fn odt_list(writer: &mut SpeechWriter, list: Node) {
+    let items: Vec<Node> = list
+        .children()
+        .filter(|node| node.has_tag_name((TEXT_NAMESPACE, "list-item")))
+        .collect();
+    let plural = if items.len() == 1 { "item" } else { "items" };
+    writer.line_break();
+    writer.text(&format!("List of {} {}:", items.len(), plural));
+    writer.soft_break();
+
+    for item in items {
+        for block in item.children().filter(Node::is_element) {
+            if block.has_tag_name((TEXT_NAMESPACE, "list")) {
+                odt_list(writer, block);
+            } else {
+                writer.inline(&odt_text(block));
+                writer.line_break();
+            }
+        }
+    }
+}
+
+fn odt_table(writer: &mut SpeechWriter, table: Node) {
+    let rows: Vec<Node> = table
+        .descendants()
+        .filter(|node| node.has_tag_name((TABLE_NAMESPACE, "table-row")))
+        .collect();
+    let cells = |row: &Node| -> Vec<String> {
+        row.children()
+            .filter(|node| node.has_tag_name((TABLE_NAMESPACE, "table-cell")))
+            .map(|cell| {
+                cell.children()
+                    .filter(Node::is_element)
+                    .map(odt_text)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    };
+
+    // Spreadsheet-style tables pad rows with empty cells, which are not counted
+    let rows: Vec<Vec<String>> = rows.iter().map(cells).collect();
+    let columns = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .rposition(|cell| !cell.trim().is_empty())
+                .map_or(0, |i| i + 1)
+        })
+        .max()
+        .unwrap_or(0);
+
+    writer.paragraph_break();
+    writer.text(&describe_table(rows.len(), columns));
+    writer.line_break();
+    for row in &rows {
+        for (index, cell) in row.iter().take(columns).enumerate() {
+            if index > 0 {
+                writer.separator();
+            }
+            writer.inline(cell);
+        }
+        writer.line_break();
+    }
+    writer.paragraph_break();
+}
+
+/// Text of a paragraph or heading, expanding spaces, tabs and line breaks
+fn odt_text(node: Node) -> String {
+    let mut text = String::new();
+    collect_odt_text(node, &mut text);
+    text
+}
+
+fn collect_odt_text(node: Node, text: &mut String) {
+    for child in node.children() {
+        if child.is_text() {
+            text.push_str(child.text().unwrap_or_default());
+            continue;
+        }
+        let namespace = child.tag_name().namespace();
+        match (namespace, child.tag_name().name()) {
+            (Some(TEXT_NAMESPACE), "s") => {
+                let count = child
+                    .attribute((TEXT_NAMESPACE, "c"))
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or(1);
+                text.push_str(&" ".repeat(count));
+            }
+            (Some(TEXT_NAMESPACE), "tab") => text.push(' '),
+            (Some(TEXT_NAMESPACE), "line-break") => text.push('\n'),
+            // Footnotes, comments and frames (images, text boxes) are not part of the flow
+            (Some(TEXT_NAMESPACE), "note" | "tracked-changes" | "bookmark-ref")
+            | (Some(OFFICE_NAMESPACE), "annotation")
+            | (Some(DRAW_NAMESPACE), _) => {}
+            _ => collect_odt_text(child, text),
+        }
+    }
+}
+
+fn is_word(node: &Node, name: &str) -> bool {
+    node.has_tag_name((WORD_NAMESPACE, name))
+}
+
+fn open_archive<'a>(bytes: &'a [u8], format: &str) -> TtsResult<ZipArchive<Cursor<&'a [u8]>>> {
+    ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| invalid(format, &format!("not a ZIP archive ({})", e)))
+}
+
+/// Read an archive entry, or `None` when it does not exist
+fn read_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+    format: &str,
+) -> TtsResult<Option<String>> {
+    let mut entry = match archive.by_name(path) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| invalid(format, &format!("cannot read '{}' ({})", path, e)))?;
+    Ok(Some(content))
+}
+
+fn parse_xml<'a>(source: &'a str, format: &str) -> TtsResult<Document<'a>> {
+    Document::parse(source).map_err(|e| invalid(format, &e.to_string()))
+}
+
+fn invalid(format: &str, reason: &str) -> TtsError {
+    TtsError::FileError(format!("Invalid {}: {}", format, reason))
+}
@@ -0,0 +1,508 @@
+//! Org-mode reading: headlines, lists, links, blocks and tables read the
+//! same way as Markdown
+//!
+//! The document is rewritten as Markdown and read by the Markdown reader,
+//! so both formats sound alike. TODO keywords, priorities, tags and planning
+//! lines are read unless `skip_todo` is set; drawers such as `:PROPERTIES:`
+//! are only read when `read_drawers` is set.
+
+use super::ReadOptions;
+use super::markdown::{self, dedent, escape, fence, push_block, table};
+
+/// Planning keywords that can follow a headline
+const PLANNING: [(&str, &str); 3] = [
+    ("SCHEDULED:", "scheduled"),
+    ("DEADLINE:", "deadline"),
+    ("CLOSED:", "closed"),
+];
+
+/// Characters that may come right before an opening emphasis marker
+const BEFORE_EMPHASIS: &str = " \t-({'\"";
+
+/// Characters that may come right after a closing emphasis marker
+const AFTER_EMPHASIS: &str = " \t-.,;:!?')}\"";
+
+/// Convert Org-mode source into text ready for speech
+pub fn to_speech(source: &str, options: &ReadOptions) -> String {
+    markdown::to_speech(&to_markdown(source, options), options)
+}
+
+/// A list being converted: the Org indentation of its bullets and the
+/// Markdown indentation of its item content
+struct ListLevel {
+    indent: usize,
+    content: usize,
+}
+
+fn to_markdown(source: &str, options: &ReadOptions) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let keywords = todo_keywords(&lines);
+    let mut out: Vec<String> = Vec::new();
+    let mut lists: Vec<ListLevel> = Vec::new();
+    // Level of a COMMENT headline whose subtree is being left out
+    let mut skipped_level: Option<usize> = None;
+    let mut blank_run = 0;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        let lower = trimmed.to_lowercase();
+        i += 1;
+
+        if let Some(level) = headline_level(line) {
+            if skipped_level.is_some_and(|skipped| level > skipped) {
+                continue;
+            }
+            skipped_level = None;
+            lists.clear();
+            let title = line[level..].trim();
+            if title == "COMMENT" || title.starts_with("COMMENT ") {
+                skipped_level = Some(level);
+                continue;
+            }
+            out.push(String::new());
+            out.push(headline(level, title, &keywords, options));
+            out.push(String::new());
+            continue;
+        }
+        if skipped_level.is_some() {
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            // Two blank lines end every open list
+            blank_run += 1;
+            if blank_run >= 2 {
+                lists.clear();
+            }
+            out.push(String::new());
+            continue;
+        }
+        blank_run = 0;
+
+        // Content indented under a list item stays in that item; anything
+        // else ends the list, and a blank line keeps it out of the last item
+        if !lists.is_empty() && list_item(trimmed, indent).is_none() {
+            while lists.last().is_some_and(|list| indent <= list.indent) {
+                lists.pop();
+            }
+            if lists.is_empty() {
+                out.push(String::new());
+            }
+        }
+        let pad = " ".repeat(lists.last().map_or(0, |list| list.content));
+
+        if let Some(block) = lower.strip_prefix("#+begin_") {
+            let name = block
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let end = format!("#+end_{}", name);
+            let start = i;
+            while i < lines.len() && !lines[i].trim().to_lowercase().starts_with(&end) {
+                i += 1;
+            }
+            let body = dedent(&lines[start..i.min(lines.len())]);
+            i += 1;
+
+            match name.as_str() {
+                "src" => {
+                    let language = trimmed.split_whitespace().nth(1).unwrap_or_default();
+                    push_block(&mut out, &pad, &fence(language, &body));
+                }
+                "example" => push_block(&mut out, &pad, &fence("", &body)),
+                "quote" | "verse" => {
+                    let quoted: Vec<String> = body
+                        .lines()
+                        .map(|line| {
+                            let text = inline(line.trim());
+                            // Verse keeps its line breaks
+                            if name == "verse" && !text.is_empty() {
+                                format!("> {}\\", text)
+                            } else {
+                                format!("> {}", text)
+                            }
+                        })
+                        .collect();
+                    push_block(&mut out, &pad, &quoted.join("\n"));
+                }
+                "comment" | "export" => {}
+                // Other blocks, such as center, are read as ordinary text
+                _ => {
+                    let converted = to_markdown(&body, options);
+                    push_block(&mut out, &pad, &converted);
+                }
+            }
+            continue;
+        }
+
+        if let Some(rest) = lower.strip_prefix("#+") {
+            let value = trimmed
+                .split_once(':')
+                .map_or("", |(_, value)| value.trim());
+            let key = rest.split(':').next().unwrap_or_default();
+            let text = match key {
+                "title" => Some(format!("# {}", inline(value))),
+                "subtitle" => Some(inline(value)),
+                "author" => Some(format!("By {}", inline(value))),
+                "caption" => Some(format!("Caption: {}", inline(value))),
+                _ => None,
+            };
+            if let Some(text) = text.filter(|_| !value.is_empty()) {
+                push_block(&mut out, &pad, &text);
+            }
+            continue;
+        }
+
+        // Comment lines
+        if trimmed == "#" || trimmed.starts_with("# ") {
+            continue;
+        }
+
+        // Fixed-width lines are read like an example block
+        if trimmed == ":" || trimmed.starts_with(": ") {
+            let start = i - 1;
+            while i < lines.len() && {
+                let next = lines[i].trim();
+                next == ":" || next.starts_with(": ")
+            } {
+                i += 1;
+            }
+            let body: Vec<&str> = lines[start..i]
+                .iter()
+                .map(|line| line.trim().trim_start_matches(':').trim())
+                .collect();
+            push_block(&mut out, &pad, &fence("", &body.join("\n")));
+            continue;
+        }
+
+        if let Some(name) = drawer_name(trimmed)
+            && let Some(length) = lines[i..]
+                .iter()
+                .position(|line| line.trim().eq_ignore_ascii_case(":END:"))
+        {
+            let body = &lines[i..i + length];
+            i += length + 1;
+            if options.read_drawers {
+                let entries: Vec<String> = body
+                    .iter()
+                    .map(|line| drawer_entry(line.trim(), &name))
+                    .filter(|entry| !entry.is_empty())
+                    .collect();
+                push_block(&mut out, &pad, &entries.join("\\\n"));
+            }
+            continue;
+        }
+
+        if PLANNING.iter().any(|(word, _)| trimmed.starts_with(word)) {
+            if !options.skip_todo {
+                push_block(&mut out, &pad, &planning(trimmed));
+            }
+            continue;
+        }
+
+        // Footnote definitions are left out, like Markdown footnotes
+        if indent == 0 && trimmed.starts_with("[fn:") {
+            while i < lines.len()
+                && !lines[i].trim().is_empty()
+                && headline_level(lines[i]).is_none()
+                && !lines[i].starts_with("[fn:")
+            {
+                i += 1;
+            }
+            continue;
+        }
+
+        if trimmed.starts_with('|') {
+            let start = i - 1;
+            while i < lines.len() && lines[i].trim_start().starts_with('|') {
+                i += 1;
+            }
+            let rows: Vec<Vec<String>> = lines[start..i]
+                .iter()
+                .map(|line| line.trim())
+                // Rule lines such as |---+---| only draw the table
+                .filter(|line| !line.starts_with("|-"))
+                .map(|line| {
+                    line.trim_matches('|')
+                        .split('|')
+                        .map(|cell| inline(cell.trim()))
+                        .collect()
+                })
+                .collect();
+            push_block(&mut out, &pad, &table(&rows));
+            continue;
+        }
+
+        if trimmed.len() >= 5 && trimmed.chars().all(|c| c == '-') {
+            out.extend([String::new(), "---".to_string(), String::new()]);
+            continue;
+        }
+
+        if let Some((marker, rest)) = list_item(trimmed, indent) {
+            while lists.last().is_some_and(|list| indent <= list.indent) {
+                lists.pop();
+            }
+            let md_indent = lists.last().map_or(0, |list| list.content);
+            let md_marker = if marker.ends_with(['.', ')']) {
+                format!("{}.", &marker[..marker.len() - 1])
+            } else {
+                "-".to_string()
+            };
+            lists.push(ListLevel {
+                indent,
+                content: md_indent + md_marker.len() + 1,
+            });
+            out.push(format!(
+                "{}{} {}",
+                " ".repeat(md_indent),
+                md_marker,
+                list_text(rest)
+            ));
+            continue;
+        }
+
+        // A trailing \\ forces a line break
+        match line.strip_suffix("\\\\") {
+            Some(text) => out.push(format!("{}{}\\", pad, inline(text.trim()))),
+            None => out.push(format!("{}{}", pad, inline(trimmed))),
+        }
+    }
+
+    out.join("\n")
+}
+
+/// The number of stars of a headline such as `** Title`
+fn headline_level(line: &str) -> Option<usize> {
+    let stars = line.len() - line.trim_start_matches('*').len();
+    (stars > 0 && line[stars..].starts_with([' ', '\t'])).then_some(stars)
+}
+
+/// TODO keywords: TODO and DONE, plus any set with `#+TODO:` lines
+fn todo_keywords(lines: &[&str]) -> Vec<String> {
+    let mut keywords = vec!["TODO".to_string(), "DONE".to_string()];
+    for line in lines {
+        let lower = line.trim().to_lowercase();
+        if ["#+todo:", "#+seq_todo:", "#+typ_todo:"]
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+        {
+            let value = line.split_once(':').map_or("", |(_, value)| value);
+            keywords.extend(
+                value
+                    .split_whitespace()
+                    .filter(|word| *word != "|")
+                    // Fast-access keys, as in DONE(d)
+                    .map(|word| word.split('(').next().unwrap_or(word).to_string()),
+            );
+        }
+    }
+    keywords
+}
+
+/// A headline as a Markdown heading, with its keyword, priority and tags
+fn headline(level: usize, title: &str, keywords: &[String], options: &ReadOptions) -> String {
+    let mut title = title;
+
+    let keyword = keywords
+        .iter()
+        .find(|keyword| {
+            title
+                .strip_prefix(keyword.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+        })
+        .cloned();
+    if let Some(keyword) = &keyword {
+        title = title[keyword.len()..].trim_start();
+    }
+
+    let mut priority = None;
+    if title.starts_with("[#") && title.get(3..4) == Some("]") {
+        priority = title.get(2..3);
+        title = title[4..].trim_start();
+    }
+
+    let mut tags = Vec::new();
+    if let Some((rest, last)) = title.rsplit_once([' ', '\t'])
+        && last.len() > 2
+        && last.starts_with(':')
+        && last.ends_with(':')
+    {
+        tags = last.trim_matches(':').split(':').collect();
+        title = rest.trim_end();
+    }
+
+    let mut text = inline(title);
+    if !options.skip_todo {
+        if let Some(keyword) = keyword {
+            let label = match keyword.as_str() {
+                "TODO" => "To do".to_string(),
+                "DONE" => "Done".to_string(),
+                other => other.to_string(),
+            };
+            text = format!("{}: {}", escape(&label), text);
+        }
+        if let Some(priority) = priority {
+            text.push_str(&escape(&format!(", priority {}", priority)));
+        }
+        if !tags.is_empty() {
+            text.push_str(&escape(&format!(", tagged {}", tags.join(", "))));
+        }
+    }
+    format!("{} {}", "#".repeat(level.min(6)), text)
+}
+
+/// The name of a drawer opened by a line such as `:PROPERTIES:`
+fn drawer_name(line: &str) -> Option<String> {
+    let name = line.strip_prefix(':')?.strip_suffix(':')?;
+    (!name.is_empty()
+        && !name.eq_ignore_ascii_case("END")
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-'))
+    .then(|| name.to_uppercase())
+}
+
+/// A drawer line: properties read as "Name: value", other drawers as they are
+fn drawer_entry(line: &str, drawer: &str) -> String {
+    if drawer == "PROPERTIES"
+        && let Some((name, value)) = line.strip_prefix(':').and_then(|rest| rest.split_once(':'))
+    {
+        return format!(
+            "{}: {}",
+            escape(name.trim_end_matches('+')),
+            inline(value.trim())
+        );
+    }
+    inline(line)
+}
+
+/// A planning line, e.g. "Scheduled 2024-03-12 Tue, deadline 2024-03-15 Fri"
+fn planning(line: &str) -> String {
+    let mut parts = Vec::new();
+    let mut rest = line;
+    while let Some((word, spoken)) = PLANNING.iter().find(|(word, _)| rest.starts_with(word)) {
+        rest = rest[word.len()..].trim_start();
+        let end = PLANNING
+            .iter()
+            .filter_map(|(next, _)| rest.find(next))
+            .min()
+            .unwrap_or(rest.len());
+        let stamp = rest[..end].trim().trim_matches(['<', '>', '[', ']']);
+        parts.push(format!("{} {}", spoken, stamp));
+        rest = rest[end..].trim_start();
+    }
+    let mut text = parts.join(", ");
+    if let Some(first) = text.get(..1) {
+        text.replace_range(..1, &first.to_uppercase());
+    }
+    escape(&text)
+}
+
+/// Split a list item into its bullet or number and its text
+fn list_item(line: &str, indent: usize) -> Option<(&str, &str)> {
+    let (marker, rest) = line.split_once(' ')?;
+    let bullet = matches!(marker, "-" | "+") || (marker == "*" && indent > 0);
+    let numbered = marker.len() > 1
+        && marker.ends_with(['.', ')'])
+        && marker[..marker.len() - 1]
+            .bytes()
+            .all(|b| b.is_ascii_digit());
+    (bullet || numbered).then_some((marker, rest.trim_start()))
+}
+
+/// The text of a list item, with its checkbox and description term
+fn list_text(text: &str) -> String {
+    let (checkbox, text) = match text.get(..4) {
+        Some("[X] ") | Some("[x] ") => ("[x] ", &text[4..]),
+        Some("[ ] ") | Some("[-] ") => ("[ ] ", &text[4..]),
+        _ => ("", text),
+    };
+    match text.split_once(" :: ") {
+        Some((term, description)) => {
+            format!("{}{}: {}", checkbox, inline(term), inline(description))
+        }
+        None => format!("{}{}", checkbox, inline(text)),
+    }
+}
+
+/// Convert inline markup: links read as their description, emphasis
+/// markers dropped, footnote references left out
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let rest: String = chars[i..].iter().collect();
+
+        if rest.starts_with("[[")
+            && let Some(end) = rest.find("]]")
+        {
+            let link = &rest[2..end];
+            match link.split_once("][") {
+                Some((_, description)) => out.push_str(&inline(description)),
+                None if link.contains("://") || link.starts_with("mailto:") => out.push_str("link"),
+                None => {
+                    let target = link
+                        .trim_start_matches("file:")
+                        .trim_start_matches("id:")
+                        .trim_start_matches(['*', '#']);
+                    out.push_str(&escape(target));
+                }
+            }
+            i += rest[..end + 2].chars().count();
+            continue;
+        }
+
+        if rest.starts_with("[fn:")
+            && let Some(end) = rest.find(']')
+        {
+            i += rest[..end + 1].chars().count();
+            continue;
+        }
+
+        // Timestamps are read without their brackets
+        if rest.starts_with(['<', '['])
+            && rest
+                .get(1..5)
+                .is_some_and(|year| year.bytes().all(|b| b.is_ascii_digit()))
+            && rest.get(5..6) == Some("-")
+            && let Some(end) = rest.find(['>', ']'])
+        {
+            out.push_str(&escape(&rest[1..end]));
+            i += rest[..end + 1].chars().count();
+            continue;
+        }
+
+        let c = chars[i];
+        if "*/_=~+".contains(c)
+            && (i == 0 || BEFORE_EMPHASIS.contains(chars[i - 1]))
+            && chars.get(i + 1).is_some_and(|next| !next.is_whitespace())
+            && let Some(close) = (i + 2..chars.len()).find(|&j| {
+                chars[j] == c
+                    && !chars[j - 1].is_whitespace()
+                    && chars
+                        .get(j + 1)
+                        .is_none_or(|after| AFTER_EMPHASIS.contains(*after))
+            })
+        {
+            let inner: String = chars[i + 1..close].iter().collect();
+            // Verbatim and code are read exactly as written
+            if c == '=' || c == '~' {
+                out.push_str(&escape(&inner));
+            } else {
+                out.push_str(&inline(&inner));
+            }
+            i = close + 1;
+            continue;
+        }
+
+        out.push_str(&escape(&c.to_string()));
+        i += 1;
+    }
+    out
+}
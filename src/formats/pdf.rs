@@ -0,0 +1,181 @@
+//! PDF reading: the text layer page by page, without running headers, footers
+//! or page numbers, and with words hyphenated across lines rejoined
+//!
+//! Scanned PDFs without a text layer are read with OCR instead.
+
+use super::{Document, NumberRange, ReadOptions, ocr};
+use crate::errors::{TtsError, TtsResult};
+use std::collections::HashMap;
+
+/// Number of lines at the top and bottom of a page checked for headers and footers
+const MARGIN_LINES: usize = 2;
+
+/// Extract the text layer of the selected pages
+pub fn to_speech(bytes: &[u8], options: &ReadOptions) -> TtsResult<Document> {
+    let pdf = lopdf::Document::load_mem(bytes)
+        .map_err(|e| TtsError::FileError(format!("Invalid PDF: {}", e)))?;
+
+    let total_pages = pdf.get_pages().len() as u32;
+    let range = options.pages.unwrap_or(NumberRange {
+        first: 1,
+        last: None,
+    });
+    let selected: Vec<u32> = (1..=total_pages)
+        .filter(|&page| range.contains(page))
+        .collect();
+    if selected.is_empty() {
+        return Err(TtsError::ConfigError(format!(
+            "Page range is outside the document (it has {} pages)",
+            total_pages
+        )));
+    }
+
+    // A page whose fonts cannot be decoded still yields the chunks that can
+    let pages: Vec<Vec<String>> = selected
+        .iter()
+        .map(|&page| {
+            pdf.extract_text_chunks(&[page])
+                .into_iter()
+                .filter_map(Result::ok)
+                .collect::<String>()
+                .lines()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|line| !line.is_empty())
+                .collect()
+        })
+        .collect();
+
+    let last_page = selected.last().copied().unwrap_or(range.first);
+    let mut stats = vec![format!(
+        "Pages: {}-{} of {}",
+        selected[0], last_page, total_pages
+    )];
+
+    if pages.iter().all(Vec::is_empty) {
+        let mut document = ocr::pdf_to_speech(bytes, selected[0], last_page, options)?;
+        stats.append(&mut document.stats);
+        document.stats = stats;
+        return Ok(document);
+    }
+
+    let (pages, removed) = remove_page_furniture(pages);
+    if removed > 0 {
+        stats.push(format!(
+            "Removed {} header, footer and page number lines",
+            removed
+        ));
+    }
+
+    Ok(Document {
+        text: join_lines(pages.into_iter().flatten()),
+        encoding: None,
+        stats,
+        item_ids: Vec::new(),
+    })
+}
+
+/// Drop repeated headers and footers and page numbers from the page margins
+///
+/// Returns the cleaned pages and how many lines were removed.
+fn remove_page_furniture(pages: Vec<Vec<String>>) -> (Vec<Vec<String>>, usize) {
+    // Count margin lines with digits masked, so "Page 3" and "Page 4" look the same
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for page in &pages {
+        let mut seen = Vec::new();
+        for (index, line) in page.iter().enumerate() {
+            if in_margin(index, page.len()) {
+                let key = mask_digits(line);
+                if !seen.contains(&key) {
+                    *counts.entry(key.clone()).or_default() += 1;
+                    seen.push(key);
+                }
+            }
+        }
+    }
+
+    // A line repeated on at least half the pages (and at least three) is furniture
+    let threshold = (pages.len().div_ceil(2)).max(3);
+    let mut removed = 0;
+    let pages = pages
+        .into_iter()
+        .map(|page| {
+            let length = page.len();
+            page.into_iter()
+                .enumerate()
+                .filter(|(index, line)| {
+                    let furniture = in_margin(*index, length)
+                        && (is_page_number(line)
+                            || counts.get(&mask_digits(line)).copied().unwrap_or(0) >= threshold);
+                    if furniture {
+                        removed += 1;
+                    }
+                    !furniture
+                })
+                .map(|(_, line)| line)
+                .collect()
+        })
+        .collect();
+
+    (pages, removed)
+}
+
+fn in_margin(index: usize, length: usize) -> bool {
+    index < MARGIN_LINES || index + MARGIN_LINES >= length
+}
+
+fn mask_digits(line: &str) -> String {
+    line.chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Lines like "12", "- 12 -", "Page 12", "Page 12 of 40", "12/40" or "xii"
+fn is_page_number(line: &str) -> bool {
+    let line = line
+        .trim()
+        .trim_matches(['-', '–', '—', ' '])
+        .to_lowercase();
+    let line = line.strip_prefix("page").unwrap_or(&line).trim();
+
+    let mut parts = line.splitn(2, [' ', '/']);
+    let first = parts.next().unwrap_or_default();
+    let rest = parts.next().map(str::trim);
+    let is_number = |text: &str| {
+        !text.is_empty() && (text.chars().all(|c| c.is_ascii_digit()) || is_roman_page(text))
+    };
+
+    match rest {
+        None => is_number(first),
+        Some(rest) => {
+            let rest = rest.strip_prefix("of").unwrap_or(rest).trim();
+            is_number(first) && rest.chars().all(|c| c.is_ascii_digit()) && !rest.is_empty()
+        }
+    }
+}
+
+/// Lower-case roman numerals from i to xxxix, as used for front matter pages
+fn is_roman_page(text: &str) -> bool {
+    let units = text.trim_start_matches('x');
+    text.len() - units.len() <= 3
+        && ["", "i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix"].contains(&units)
+        && !text.is_empty()
+}
+
+/// Join lines, rejoining words hyphenated across line (and page) breaks
+fn join_lines(lines: impl Iterator<Item = String>) -> String {
+    let mut text = String::new();
+    for line in lines {
+        let hyphenated = text
+            .strip_suffix('-')
+            .and_then(|before| before.chars().last())
+            .is_some_and(char::is_alphabetic);
+        if hyphenated && line.starts_with(char::is_lowercase) {
+            text.pop();
+        } else if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&line);
+    }
+    text
+}
@@ -0,0 +1,784 @@
+//! reStructuredText reading: sections, lists, links, directives, literal
+//! blocks and tables read the same way as Markdown
+//!
+//! Like Org-mode, the document is rewritten as Markdown and read by the
+//! Markdown reader. Admonitions are announced ("Note: ..."), math is spoken
+//! like LaTeX math, and directives with nothing to read, such as `toctree`,
+//! are left out.
+
+use super::markdown::{self, dedent, escape, fence, push_block, table};
+use super::{ReadOptions, math};
+
+/// Characters that can underline or overline a section title
+const ADORNMENTS: &str = "=-~^\"'`#*+:.!$%&,;<>?@\\/_|";
+
+/// Characters that may come right before inline markup
+const BEFORE_MARKUP: &str = " \t'\"([{<-/:";
+
+/// Directives that produce nothing worth reading
+const SKIPPED_DIRECTIVES: &[&str] = &[
+    "include",
+    "literalinclude",
+    "toctree",
+    "contents",
+    "index",
+    "meta",
+    "raw",
+    "highlight",
+    "sectnum",
+    "tabularcolumns",
+    "default-role",
+    "role",
+    "currentmodule",
+    "module",
+    "target-notes",
+    "header",
+    "footer",
+];
+
+/// Admonitions and how they are announced
+const ADMONITIONS: &[(&str, &str)] = &[
+    ("note", "Note"),
+    ("warning", "Warning"),
+    ("tip", "Tip"),
+    ("hint", "Hint"),
+    ("important", "Important"),
+    ("caution", "Caution"),
+    ("danger", "Danger"),
+    ("attention", "Attention"),
+    ("error", "Error"),
+    ("seealso", "See also"),
+    ("todo", "To do"),
+    ("deprecated", "Deprecated since version"),
+    ("versionadded", "New in version"),
+    ("versionchanged", "Changed in version"),
+];
+
+/// Convert reStructuredText source into text ready for speech
+pub fn to_speech(source: &str, options: &ReadOptions) -> String {
+    let expanded = source.replace('\t', "        ");
+    let lines: Vec<&str> = expanded.lines().map(str::trim_end).collect();
+    let mut converter = Converter { styles: Vec::new() };
+    let markdown = converter.convert(&lines).join("\n");
+    markdown::to_speech(&markdown, options)
+}
+
+struct Converter {
+    /// Title adornments in order of first use, which sets their heading level
+    styles: Vec<(char, bool)>,
+}
+
+impl Converter {
+    /// Convert a run of body elements, all starting at column zero
+    fn convert(&mut self, lines: &[&str]) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            if line.is_empty() {
+                out.push(String::new());
+                i += 1;
+                continue;
+            }
+
+            // Indented text on its own is a block quote
+            if line.starts_with(' ') {
+                let end = indented_end(lines, i);
+                let body = dedent_lines(&lines[i..end]);
+                let quoted: Vec<String> = self
+                    .convert(&body)
+                    .into_iter()
+                    .map(|line| format!("> {}", line))
+                    .collect();
+                push_block(&mut out, "", &quoted.join("\n"));
+                i = end;
+                continue;
+            }
+
+            if let Some((title, length)) = self.section(lines, i) {
+                out.extend([String::new(), title, String::new()]);
+                i += length;
+                continue;
+            }
+
+            if adornment(line).is_some() && line.len() >= 4 {
+                out.extend([String::new(), "---".to_string(), String::new()]);
+                i += 1;
+                continue;
+            }
+
+            if line == ".." || line.starts_with(".. ") {
+                let end = indented_end(lines, i + 1);
+                self.explicit_markup(&lines[i..end], &mut out);
+                i = end;
+                continue;
+            }
+
+            if line.starts_with("+-") || line.starts_with("+=") {
+                let end = block_end(lines, i);
+                push_block(&mut out, "", &table(&grid_table(&lines[i..end])));
+                i = end;
+                continue;
+            }
+
+            if is_simple_table_rule(line) {
+                let end = simple_table_end(lines, i);
+                push_block(&mut out, "", &table(&simple_table(&lines[i..end])));
+                i = end;
+                continue;
+            }
+
+            if line == "|" || line.starts_with("| ") {
+                let end = block_end(lines, i);
+                let verses: Vec<String> = lines[i..end]
+                    .iter()
+                    .map(|line| inline(line.trim_start_matches('|').trim()))
+                    .collect();
+                push_block(&mut out, "", &verses.join("\\\n"));
+                i = end;
+                continue;
+            }
+
+            if let Some((marker, number)) = list_marker(line) {
+                i = self.list(lines, i, marker, number, &mut out);
+                continue;
+            }
+
+            if is_field(line) {
+                let mut fields = Vec::new();
+                while i < lines.len() && is_field(lines[i]) {
+                    let end = indented_end(lines, i + 1);
+                    let (name, value) = lines[i][1..]
+                        .split_once(": ")
+                        .unwrap_or((&lines[i][1..], ""));
+                    let mut value = vec![value.trim()];
+                    value.extend(lines[i + 1..end].iter().map(|line| line.trim()));
+                    fields.push(format!(
+                        "{}: {}",
+                        escape(name.trim_end_matches(':')),
+                        inline(value.join(" ").trim())
+                    ));
+                    i = end;
+                }
+                push_block(&mut out, "", &fields.join("\\\n"));
+                continue;
+            }
+
+            // Doctest blocks
+            if line.starts_with(">>>") {
+                let end = block_end(lines, i);
+                push_block(&mut out, "", &fence("python", &lines[i..end].join("\n")));
+                i = end;
+                continue;
+            }
+
+            // A line followed directly by indented text is a definition list item
+            if !line.ends_with("::") && lines.get(i + 1).is_some_and(|next| next.starts_with(' ')) {
+                let end = indented_end(lines, i + 1);
+                let term = line.split(" : ").next().unwrap_or(line);
+                let body = self.convert(&dedent_lines(&lines[i + 1..end]));
+                out.extend(labelled(&format!("{}:", inline(term)), body));
+                i = end;
+                continue;
+            }
+
+            // A paragraph, possibly introducing a literal block with "::"
+            let end = (i..lines.len())
+                .find(|&j| lines[j].is_empty() || lines[j].starts_with(' '))
+                .unwrap_or(lines.len());
+            let mut text = lines[i..end].join(" ");
+            let literal = text.ends_with("::");
+            if literal {
+                text.truncate(text.len() - 2);
+                let trimmed = text.trim_end().len();
+                if trimmed == text.len() && !text.is_empty() {
+                    text.push(':');
+                } else {
+                    text.truncate(trimmed);
+                }
+            }
+            if !text.is_empty() {
+                push_block(&mut out, "", &inline(&text));
+            }
+            i = end;
+
+            if literal {
+                let start = (i..lines.len())
+                    .find(|&j| !lines[j].is_empty())
+                    .unwrap_or(lines.len());
+                if lines.get(start).is_some_and(|line| line.starts_with(' ')) {
+                    let end = indented_end(lines, start);
+                    push_block(&mut out, "", &fence("", &dedent(&lines[start..end])));
+                    i = end;
+                }
+            }
+        }
+        out
+    }
+
+    /// A section title at `i`, with or without an overline; returns the
+    /// Markdown heading and the number of lines it took
+    fn section(&mut self, lines: &[&str], i: usize) -> Option<(String, usize)> {
+        let line = lines[i];
+        let (title, style, length) = match adornment(line) {
+            // Overline, title, underline
+            Some(c) => {
+                let title = lines.get(i + 1)?.trim();
+                let under = lines.get(i + 2)?;
+                if title.is_empty() || adornment(under) != Some(c) {
+                    return None;
+                }
+                (title, (c, true), 3)
+            }
+            None => {
+                let under = lines.get(i + 1)?;
+                let c = adornment(under)?;
+                // Underlines shorter than the title still count from four characters
+                let title_length = line.chars().count();
+                if under.len() < title_length.clamp(2, 4) {
+                    return None;
+                }
+                (line.trim(), (c, false), 2)
+            }
+        };
+
+        let level = match self.styles.iter().position(|&s| s == style) {
+            Some(level) => level + 1,
+            None => {
+                self.styles.push(style);
+                self.styles.len()
+            }
+        };
+        Some((
+            format!("{} {}", "#".repeat(level.min(6)), inline(title)),
+            length,
+        ))
+    }
+
+    /// Directives, and the comments, targets and footnotes that are left out
+    fn explicit_markup(&mut self, block: &[&str], out: &mut Vec<String>) {
+        let first = block[0].get(3..).unwrap_or_default();
+        let Some((name, arguments)) = first.split_once("::") else {
+            return;
+        };
+        let name = name.trim().to_lowercase();
+        if name.is_empty()
+            || name.contains(' ')
+            || name.starts_with(['_', '[', '|'])
+            || SKIPPED_DIRECTIVES.contains(&name.as_str())
+            || name.starts_with("auto")
+        {
+            return;
+        }
+        let arguments = arguments.trim();
+
+        // Options come right after the directive line, then the content
+        let body = dedent_lines(&block[1..]);
+        let mut options = Vec::new();
+        let mut start = 0;
+        while start < body.len() && body[start].starts_with(':') {
+            if let Some((key, value)) = body[start][1..].split_once(':') {
+                options.push((key.to_lowercase(), value.trim().to_string()));
+            }
+            start += 1;
+        }
+        let content = &body[start..];
+        let option = |key: &str| {
+            options
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        };
+
+        if let Some((_, label)) = ADMONITIONS
+            .iter()
+            .find(|(admonition, _)| *admonition == name)
+        {
+            // Admonition text may start on the directive line itself
+            let mut lines = vec![arguments];
+            lines.extend_from_slice(content);
+            let heading = if name.starts_with("version") || name == "deprecated" {
+                let (version, text) = arguments.split_once(' ').unwrap_or((arguments, ""));
+                lines = vec![text];
+                lines.extend_from_slice(content);
+                format!("{} {}:", label, version)
+            } else {
+                format!("{}:", label)
+            };
+            let body = self.convert(&trim_blank(&lines));
+            out.extend(labelled(&escape(&heading), body));
+            return;
+        }
+
+        match name.as_str() {
+            "code" | "code-block" | "sourcecode" => {
+                let language = arguments.split_whitespace().next().unwrap_or_default();
+                push_block(out, "", &fence(language, &content.join("\n")));
+            }
+            "math" => {
+                let source = if arguments.is_empty() {
+                    content.join("\n")
+                } else {
+                    arguments.to_string()
+                };
+                for equation in source.split("\n\n") {
+                    let spoken = math::verbalize(equation);
+                    if !spoken.is_empty() {
+                        push_block(out, "", &escape(&format!("Equation: {}", spoken)));
+                    }
+                }
+            }
+            "image" | "figure" => {
+                let alt = option("alt").unwrap_or_default();
+                push_block(out, "", &format!("![{}](image)", inline(alt)));
+                out.extend(self.convert(&trim_blank(content)));
+            }
+            "list-table" => push_block(out, "", &table(&list_table(content))),
+            "csv-table" => {
+                let mut rows = Vec::new();
+                if let Some(header) = option("header") {
+                    rows.extend(csv_rows(header));
+                }
+                rows.extend(csv_rows(&content.join("\n")));
+                push_block(out, "", &table(&rows));
+            }
+            // Titled containers: the title, then whatever they hold
+            _ => {
+                if !arguments.is_empty() {
+                    push_block(out, "", &inline(arguments));
+                }
+                out.extend(self.convert(&trim_blank(content)));
+            }
+        }
+    }
+
+    /// Convert the list starting at `i`; returns the line after it
+    fn list(
+        &mut self,
+        lines: &[&str],
+        mut i: usize,
+        marker: String,
+        number: Option<u64>,
+        out: &mut Vec<String>,
+    ) -> usize {
+        out.push(String::new());
+        let mut next_number = number;
+        let mut current = Some((marker, number));
+
+        while let Some((marker, number)) = current.take() {
+            let end = indented_end(lines, i + 1);
+            let mut item = vec![lines[i][marker.len()..].trim_start()];
+            item.extend(dedent_lines(&lines[i + 1..end]));
+            let body = self.convert(&trim_blank(&item));
+
+            let md_marker = match number {
+                Some(_) => format!("{}.", next_number.unwrap_or(1)),
+                None => "-".to_string(),
+            };
+            let pad = " ".repeat(md_marker.len() + 1);
+            let mut body = body.iter().skip_while(|line| line.is_empty());
+            out.push(format!(
+                "{} {}",
+                md_marker,
+                body.next().map(String::as_str).unwrap_or_default()
+            ));
+            for line in body {
+                if line.is_empty() {
+                    out.push(String::new());
+                } else {
+                    out.push(format!("{}{}", pad, line));
+                }
+            }
+            next_number = next_number.map(|n| n + 1);
+            i = end;
+
+            // The list goes on while the next item has the same kind of marker
+            let next = (i..lines.len()).find(|&j| !lines[j].is_empty());
+            if let Some(j) = next
+                && let Some((next_marker, next_num)) = list_marker(lines[j])
+                && next_num.is_some() == number.is_some()
+                && (number.is_some() || next_marker == marker)
+            {
+                i = j;
+                current = Some((next_marker, next_num));
+            }
+        }
+        out.push(String::new());
+        i
+    }
+}
+
+/// Put a label such as "Note:" in front of converted text, on the same line
+/// when the text starts with a paragraph
+fn labelled(label: &str, mut body: Vec<String>) -> Vec<String> {
+    let first = body.iter().position(|line| !line.is_empty());
+    match first {
+        // Escaped text starts with a letter, a digit or a backslash
+        Some(index) if body[index].starts_with(|c: char| c.is_alphanumeric() || c == '\\') => {
+            body[index] = format!("{} {}", label, body[index]);
+        }
+        _ => body.insert(0, label.to_string()),
+    }
+    body.insert(0, String::new());
+    body
+}
+
+/// The adornment character of a line made of one repeated punctuation mark
+fn adornment(line: &str) -> Option<char> {
+    let c = line.chars().next()?;
+    (ADORNMENTS.contains(c) && line.chars().all(|other| other == c)).then_some(c)
+}
+
+/// A bullet or enumerator at the start of a line, and its number if it has one
+fn list_marker(line: &str) -> Option<(String, Option<u64>)> {
+    let (marker, rest) = line.split_once(' ').unwrap_or((line, ""));
+    if rest.is_empty() && !matches!(marker, "-" | "*" | "+" | "•") {
+        return None;
+    }
+    if matches!(marker, "-" | "*" | "+" | "•") {
+        return Some((marker.to_string(), None));
+    }
+
+    let label = marker
+        .strip_prefix('(')
+        .and_then(|m| m.strip_suffix(')'))
+        .or_else(|| marker.strip_suffix(['.', ')']))?;
+    let number = if label == "#" {
+        1
+    } else if let Ok(number) = label.parse::<u64>() {
+        number
+    } else if label.len() == 1 && label.chars().all(|c| c.is_ascii_alphabetic()) {
+        u64::from(label.to_ascii_lowercase().as_bytes()[0] - b'a' + 1)
+    } else {
+        return None;
+    };
+    Some((marker.to_string(), Some(number)))
+}
+
+/// A field list line such as `:Author: Jane Doe`
+fn is_field(line: &str) -> bool {
+    line.strip_prefix(':')
+        .and_then(|rest| rest.split_once(':'))
+        .is_some_and(|(name, rest)| {
+            !name.is_empty() && !name.contains('`') && (rest.is_empty() || rest.starts_with(' '))
+        })
+        // Inline roles such as :ref:`x` are not fields
+        && !line.contains(":`")
+}
+
+fn is_simple_table_rule(line: &str) -> bool {
+    line.starts_with('=')
+        && line.split_whitespace().count() >= 2
+        && line.chars().all(|c| c == '=' || c == ' ')
+}
+
+/// The line after an indented block that starts at `start`, leaving out
+/// trailing blank lines
+fn indented_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start;
+    let mut last_text = start;
+    while end < lines.len() && (lines[end].is_empty() || lines[end].starts_with(' ')) {
+        end += 1;
+        if !lines[end - 1].is_empty() {
+            last_text = end;
+        }
+    }
+    last_text.max(start)
+}
+
+/// The first blank line at or after `start`
+fn block_end(lines: &[&str], start: usize) -> usize {
+    (start..lines.len())
+        .find(|&j| lines[j].is_empty())
+        .unwrap_or(lines.len())
+}
+
+/// Lines with their common indentation removed
+fn dedent_lines<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect()
+}
+
+fn trim_blank<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    let start = lines.iter().position(|line| !line.trim().is_empty());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => dedent_lines(&lines[start..=end]),
+        _ => Vec::new(),
+    }
+}
+
+/// Rows of a grid table; cell lines between borders are joined
+fn grid_table(lines: &[&str]) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<Vec<&str>> = Vec::new();
+    for line in lines {
+        if line.starts_with('+') {
+            if !row.is_empty() {
+                rows.push(row.iter().map(|cell| inline(&cell.join(" "))).collect());
+                row.clear();
+            }
+            continue;
+        }
+        let cells: Vec<&str> = line.trim_matches('|').split('|').map(str::trim).collect();
+        row.resize(row.len().max(cells.len()), Vec::new());
+        for (index, cell) in cells.into_iter().enumerate() {
+            if !cell.is_empty() {
+                row[index].push(cell);
+            }
+        }
+    }
+    rows
+}
+
+/// The line after a simple table: its closing rule is the last rule before a blank line
+fn simple_table_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start + 1;
+    while end < lines.len() {
+        if is_simple_table_rule(lines[end]) && lines.get(end + 1).is_none_or(|next| next.is_empty())
+        {
+            return end + 1;
+        }
+        end += 1;
+    }
+    end
+}
+
+/// Rows of a simple table, split at the columns of its first rule
+fn simple_table(lines: &[&str]) -> Vec<Vec<String>> {
+    let rule = lines[0];
+    let starts: Vec<usize> = rule
+        .char_indices()
+        .filter(|&(index, c)| c == '=' && (index == 0 || rule[..index].ends_with(' ')))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for line in &lines[1..] {
+        if line.is_empty() || is_simple_table_rule(line) || line.trim_start().starts_with("---") {
+            continue;
+        }
+        let chars: Vec<char> = line.chars().collect();
+        let cells: Vec<String> = starts
+            .iter()
+            .enumerate()
+            .map(|(column, &start)| {
+                let end = starts.get(column + 1).copied().unwrap_or(chars.len());
+                chars[start.min(chars.len())..end.min(chars.len())]
+                    .iter()
+                    .collect::<String>()
+                    .trim()
+                    .to_string()
+            })
+            .collect();
+        // A blank first column continues the row above
+        match rows.last_mut() {
+            Some(previous) if cells[0].is_empty() => {
+                for (cell, more) in previous.iter_mut().zip(cells) {
+                    if !more.is_empty() {
+                        cell.push(' ');
+                        cell.push_str(&more);
+                    }
+                }
+            }
+            _ => rows.push(cells),
+        }
+    }
+    rows.into_iter()
+        .map(|row| row.iter().map(|cell| inline(cell)).collect())
+        .collect()
+}
+
+/// Rows of a `list-table` directive: `* -` starts a row, `-` a cell
+fn list_table(content: &[&str]) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for line in content {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("* ") {
+            rows.push(Vec::new());
+            if let Some(cell) = rest.trim_start().strip_prefix("- ") {
+                rows.last_mut()
+                    .into_iter()
+                    .for_each(|row| row.push(cell.to_string()));
+            }
+        } else if let Some(cell) = trimmed
+            .strip_prefix("- ")
+            .or((trimmed == "-").then_some(""))
+        {
+            if let Some(row) = rows.last_mut() {
+                row.push(cell.to_string());
+            }
+        } else if !trimmed.is_empty()
+            && let Some(cell) = rows.last_mut().and_then(|row| row.last_mut())
+        {
+            cell.push(' ');
+            cell.push_str(trimmed);
+        }
+    }
+    rows.into_iter()
+        .map(|row| row.iter().map(|cell| inline(cell.trim())).collect())
+        .collect()
+}
+
+fn csv_rows(source: &str) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(source.as_bytes())
+        .records()
+        .filter_map(Result::ok)
+        .map(|record| record.iter().map(inline).collect())
+        .collect()
+}
+
+/// Convert inline markup: links and roles read as their text, emphasis
+/// markers dropped, footnote references left out
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    let starts_markup = |i: usize| i == 0 || BEFORE_MARKUP.contains(chars[i - 1]);
+    let find = |from: usize, pattern: &[char]| {
+        (from..chars.len()).find(|&j| chars[j..].starts_with(pattern))
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\\' {
+            // An escaped space joins words, as in "RST\ s"
+            if let Some(&next) = chars.get(i + 1)
+                && next != ' '
+            {
+                out.push_str(&escape(&next.to_string()));
+            }
+            i += 2;
+            continue;
+        }
+
+        if chars[i..].starts_with(&['`', '`'])
+            && starts_markup(i)
+            && let Some(close) = find(i + 2, &['`', '`'])
+        {
+            let literal: String = chars[i + 2..close].iter().collect();
+            out.push_str(&escape(&literal));
+            i = close + 2;
+            continue;
+        }
+
+        // Roles such as :ref:`Title <target>` and :math:`x^2`
+        if c == ':'
+            && starts_markup(i)
+            && let Some(tick) = find(i + 1, &[':', '`'])
+            && chars[i + 1..tick]
+                .iter()
+                .all(|c| c.is_alphanumeric() || "-_.+:".contains(*c))
+            && let Some(close) = find(tick + 2, &['`'])
+        {
+            let role: String = chars[i + 1..tick].iter().collect();
+            let content: String = chars[tick + 2..close].iter().collect();
+            out.push_str(&role_text(&role, &content));
+            i = close + 1;
+            continue;
+        }
+
+        if c == '`'
+            && starts_markup(i)
+            && let Some(close) = find(i + 1, &['`'])
+        {
+            let content: String = chars[i + 1..close].iter().collect();
+            let mut end = close + 1;
+            while chars.get(end) == Some(&'_') {
+                end += 1;
+            }
+            let is_link = end > close + 1;
+            out.push_str(&match content.rsplit_once('<') {
+                Some((title, _)) if is_link && !title.trim().is_empty() => inline(title.trim()),
+                Some(_) if is_link => "link".to_string(),
+                _ => inline(&content),
+            });
+            i = end;
+            continue;
+        }
+
+        if "*|".contains(c) && starts_markup(i) {
+            let strong = c == '*' && chars.get(i + 1) == Some(&'*');
+            let marker: &[char] = if strong {
+                &['*', '*']
+            } else {
+                &chars[i..i + 1]
+            };
+            let open = i + marker.len();
+            if chars.get(open).is_some_and(|next| !next.is_whitespace())
+                && let Some(close) = find(open + 1, marker)
+                && !chars[close - 1].is_whitespace()
+            {
+                let content: String = chars[open..close].iter().collect();
+                out.push_str(&inline(&content));
+                i = close + marker.len();
+                // A substitution can also be a reference, as in |name|_
+                while c == '|' && chars.get(i) == Some(&'_') {
+                    i += 1;
+                }
+                continue;
+            }
+        }
+
+        // Footnote and citation references such as [1]_ and [#note]_
+        if c == '['
+            && let Some(close) = find(i + 1, &[']', '_'])
+            && chars[i + 1..close].iter().all(|c| !c.is_whitespace())
+        {
+            // Drop the space before the reference too
+            if out.ends_with(' ') {
+                out.pop();
+            }
+            i = close + 2;
+            continue;
+        }
+
+        // Trailing underscores of references such as Python_ and Python__
+        if c == '_' && i > 0 && chars[i - 1].is_alphanumeric() {
+            let end = (i..chars.len())
+                .find(|&j| chars[j] != '_')
+                .unwrap_or(chars.len());
+            if chars
+                .get(end)
+                .is_none_or(|next| next.is_whitespace() || ".,;:!?)".contains(*next))
+            {
+                i = end;
+                continue;
+            }
+        }
+
+        out.push_str(&escape(&c.to_string()));
+        i += 1;
+    }
+    out
+}
+
+/// The text of an interpreted role
+fn role_text(role: &str, content: &str) -> String {
+    if role == "math" {
+        return escape(&math::verbalize(content));
+    }
+    // Cross-references: "Title <target>" reads the title; "~a.b.c" reads "c"
+    if let Some((title, _)) = content.rsplit_once(" <") {
+        return inline(title.trim());
+    }
+    let target = content.trim_start_matches('!');
+    match target.strip_prefix('~') {
+        Some(path) => escape(path.rsplit('.').next().unwrap_or(path)),
+        None => escape(target),
+    }
+}
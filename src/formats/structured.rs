@@ -0,0 +1,272 @@
+//! JSON, YAML and TOML reading as a spoken outline: every value is read
+//! with its key path, e.g. "server, max connections: 20.", sections and
+//! lists announce their size and nesting level.
+
+use super::ReadOptions;
+use super::code::split_identifier;
+use crate::errors::{TtsError, TtsResult};
+use serde_json::{Map, Number, Value};
+use yaml_rust2::{Yaml, YamlLoader};
+
+/// Structured data syntaxes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Syntax {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Syntax::Json => "JSON",
+            Syntax::Yaml => "YAML",
+            Syntax::Toml => "TOML",
+        }
+    }
+}
+
+/// Read a document as an outline; returns the text and stats about the selection
+pub fn to_speech(
+    source: &str,
+    syntax: Syntax,
+    options: &ReadOptions,
+) -> TtsResult<(String, Vec<String>)> {
+    let documents = parse(source, syntax)?;
+    let mut lines = Vec::new();
+    let mut stats = Vec::new();
+
+    for (index, document) in documents.iter().enumerate() {
+        if documents.len() > 1 {
+            lines.push(format!("Document {} of {}.", index + 1, documents.len()));
+        }
+        let (value, path) = match &options.select {
+            Some(select) => find(document, select)?,
+            None => (document, Vec::new()),
+        };
+        outline(value, &path, &mut lines);
+    }
+
+    if documents.len() > 1 {
+        stats.push(format!("Documents: {}", documents.len()));
+    }
+    if let Some(select) = &options.select {
+        stats.push(format!("Selected: {}", select));
+    }
+    Ok((lines.join("\n"), stats))
+}
+
+/// Parse the source into one value per document (YAML streams can hold several)
+fn parse(source: &str, syntax: Syntax) -> TtsResult<Vec<Value>> {
+    let invalid =
+        |error: String| TtsError::FileError(format!("Invalid {}: {}", syntax.name(), error));
+
+    match syntax {
+        Syntax::Json => serde_json::from_str(source)
+            .map(|value| vec![value])
+            .map_err(|e| invalid(e.to_string())),
+        Syntax::Toml => source
+            .parse::<toml::Table>()
+            .map(|table| vec![from_toml(toml::Value::Table(table))])
+            .map_err(|e| invalid(e.to_string())),
+        Syntax::Yaml => YamlLoader::load_from_str(source)
+            .map(|documents| documents.into_iter().map(from_yaml).collect())
+            .map_err(|e| invalid(e.to_string())),
+    }
+}
+
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(number) => Value::from(number),
+        toml::Value::Float(number) => float(number),
+        toml::Value::Boolean(flag) => Value::Bool(flag),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, from_toml(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn from_yaml(value: Yaml) -> Value {
+    match value {
+        Yaml::Null | Yaml::Alias(_) | Yaml::BadValue => Value::Null,
+        Yaml::Boolean(flag) => Value::Bool(flag),
+        Yaml::Integer(number) => Value::from(number),
+        // Integers too large for i64 come through as reals
+        Yaml::Real(text) => match text.parse::<u64>() {
+            Ok(number) => Value::from(number),
+            // The loader only makes reals of text that parses as one
+            Err(_) => float(Yaml::Real(text).as_f64().unwrap_or_default()),
+        },
+        Yaml::String(text) => Value::String(text),
+        Yaml::Array(items) => Value::Array(items.into_iter().map(from_yaml).collect()),
+        // Keys can be any scalar in YAML; read them as written
+        Yaml::Hash(mapping) => Value::Object(
+            mapping
+                .into_iter()
+                .map(|(key, value)| (scalar_text(&from_yaml(key)), from_yaml(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+/// JSON numbers can't hold NaN or infinity, so those are kept as text
+fn float(number: f64) -> Value {
+    Number::from_f64(number).map_or_else(|| Value::String(number.to_string()), Value::Number)
+}
+
+/// Follow a `--select` path such as `server.hosts[0].name`; returns the
+/// value found and its spoken path, with list items counted from 1
+fn find<'a>(document: &'a Value, select: &str) -> TtsResult<(&'a Value, Vec<String>)> {
+    let mut value = document;
+    let mut walked = Vec::new();
+    let mut spoken = Vec::new();
+
+    for segment in path_segments(select) {
+        let here = if walked.is_empty() {
+            "the top level".to_string()
+        } else {
+            format!("'{}'", walked.join("."))
+        };
+        spoken.push(match value {
+            Value::Array(_) => format!("item {}", segment.parse::<usize>().unwrap_or(0) + 1),
+            _ => spoken_key(&segment),
+        });
+        value = match value {
+            Value::Object(map) => map.get(&segment).ok_or_else(|| {
+                let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+                TtsError::ConfigError(format!(
+                    "No key '{}' under {} (the keys are: {})",
+                    segment,
+                    here,
+                    keys.join(", ")
+                ))
+            })?,
+            Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get(index))
+                .ok_or_else(|| {
+                    TtsError::ConfigError(format!(
+                        "No item '{}' under {} (it is a list of {}, numbered from 0)",
+                        segment,
+                        here,
+                        count(items.len(), "item")
+                    ))
+                })?,
+            _ => {
+                return Err(TtsError::ConfigError(format!(
+                    "Can't select '{}' under {}, which is a single value",
+                    segment, here
+                )));
+            }
+        };
+        walked.push(segment);
+    }
+    Ok((value, spoken))
+}
+
+/// Split `a.b[0].c` into `a`, `b`, `0`, `c`
+fn path_segments(select: &str) -> Vec<String> {
+    select
+        .split(['.', '[', ']'])
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn spoken_key(key: &str) -> String {
+    split_identifier(key).join(" ")
+}
+
+/// Read a value and everything under it, one line per entry
+fn outline(value: &Value, path: &[String], lines: &mut Vec<String>) {
+    let label = path.join(", ");
+    let level = path.len();
+
+    match value {
+        Value::Object(map) if map.is_empty() => lines.push(entry(&label, "empty section")),
+        Value::Object(map) => {
+            if level > 0 {
+                lines.push(entry(
+                    &label,
+                    &format!("section with {}, level {}", count(map.len(), "key"), level),
+                ));
+            }
+            for (key, child) in map {
+                let mut child_path = path.to_vec();
+                child_path.push(spoken_key(key));
+                outline(child, &child_path, lines);
+            }
+        }
+        Value::Array(items) if items.is_empty() => lines.push(entry(&label, "empty list")),
+        // Lists of plain values are read in one go
+        Value::Array(items) if items.iter().all(is_scalar) => {
+            let values: Vec<String> = items.iter().map(scalar_text).collect();
+            lines.push(entry(
+                &label,
+                &format!(
+                    "list of {}: {}",
+                    count(items.len(), "item"),
+                    values.join(", ")
+                ),
+            ));
+        }
+        Value::Array(items) => {
+            let size = format!("list of {}", count(items.len(), "item"));
+            if level > 0 {
+                lines.push(entry(&label, &format!("{}, level {}", size, level)));
+            } else {
+                lines.push(entry(&label, &size));
+            }
+            for (index, child) in items.iter().enumerate() {
+                let mut child_path = path.to_vec();
+                child_path.push(format!("item {}", index + 1));
+                outline(child, &child_path, lines);
+            }
+        }
+        scalar => lines.push(entry(&label, &scalar_text(scalar))),
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::String(text) if text.trim().is_empty() => "empty".to_string(),
+        // Block strings are read as one sentence
+        Value::String(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        other => other.to_string(),
+    }
+}
+
+/// One outline line, e.g. "server, port: 8080."
+fn entry(label: &str, text: &str) -> String {
+    let line = if label.is_empty() {
+        // A value at the top level starts the sentence
+        let mut chars = text.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        format!("{}: {}", label, text)
+    };
+    if line.ends_with(['.', '!', '?']) {
+        line
+    } else {
+        format!("{}.", line)
+    }
+}
+
+fn count(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
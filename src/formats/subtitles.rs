@@ -0,0 +1,141 @@
+//! Subtitle reading: SubRip (`.srt`) and WebVTT (`.vtt`) cues
+//!
+//! Only the cue text is read; numbering, timestamps, styling and sound
+//! descriptions such as "[music]" are left out.
+
+use super::SpeechWriter;
+use super::html::decode_entities;
+use crate::errors::{TtsError, TtsResult};
+use std::time::Duration;
+
+/// One subtitle cue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+}
+
+/// Parse the cues of an SRT or WebVTT file, in file order
+pub fn parse(source: &str) -> TtsResult<Vec<Cue>> {
+    let source = source.replace("\r\n", "\n").replace('\r', "\n");
+    let mut cues = Vec::new();
+
+    for block in source.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        // Header, NOTE, STYLE and REGION blocks have no timing line
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let (start, end) = parse_timing(timing)?;
+        let text = clean_text(lines);
+        if !text.is_empty() {
+            cues.push(Cue { start, end, text });
+        }
+    }
+
+    if cues.is_empty() {
+        return Err(TtsError::FileError("No subtitle cues found".to_string()));
+    }
+    Ok(cues)
+}
+
+/// Read the cue text only, one cue per line
+pub fn to_speech(source: &str) -> TtsResult<String> {
+    let mut writer = SpeechWriter::new();
+    for cue in parse(source)? {
+        // Dialogue lines stay apart, so a change of speaker is heard
+        for line in cue.text.split('\n') {
+            writer.inline(line);
+            writer.line_break();
+        }
+    }
+    Ok(writer.finish())
+}
+
+/// Parse "00:01:02,500 --> 00:01:04,000" with any WebVTT cue settings after it
+fn parse_timing(line: &str) -> TtsResult<(Duration, Duration)> {
+    let invalid = || TtsError::FileError(format!("Invalid subtitle timing '{}'", line.trim()));
+    let (start, rest) = line.split_once("-->").ok_or_else(invalid)?;
+    let end = rest.split_whitespace().next().ok_or_else(invalid)?;
+    let start = parse_timestamp(start.trim()).ok_or_else(invalid)?;
+    let end = parse_timestamp(end).ok_or_else(invalid)?;
+    Ok((start, end.max(start)))
+}
+
+/// Parse "HH:MM:SS,mmm", "HH:MM:SS.mmm" or WebVTT's "MM:SS.mmm"
+fn parse_timestamp(text: &str) -> Option<Duration> {
+    let (clock, millis) = text.split_once([',', '.'])?;
+    let millis: u64 = millis.parse().ok().filter(|_| millis.len() == 3)?;
+
+    let parts = clock
+        .split(':')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let seconds = match parts.as_slice() {
+        [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+        [minutes, seconds] => minutes * 60 + seconds,
+        _ => return None,
+    };
+    Some(Duration::from_millis(seconds * 1000 + millis))
+}
+
+/// Strip markup from cue lines and join them for speech
+///
+/// Lines are joined with spaces, except for dialogue lines starting with a
+/// dash, which mark a change of speaker and stay on their own lines.
+fn clean_text<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let mut text = String::new();
+    for line in lines {
+        let line = strip_markup(line);
+        let line = line.trim();
+        let (dialogue, line) = match line.strip_prefix('-') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+        if line.is_empty() {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push(if dialogue { '\n' } else { ' ' });
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+/// Remove tags (`<i>`, `<v Speaker>`, timestamps), SSA overrides (`{\an8}`),
+/// sound descriptions in square brackets and music notes
+fn strip_markup(line: &str) -> String {
+    let mut out = String::new();
+    let mut closing = None;
+    for c in line.chars() {
+        match closing {
+            Some(close) if c == close => closing = None,
+            Some(_) => {}
+            None => match c {
+                '<' => closing = Some('>'),
+                '{' => closing = Some('}'),
+                '[' => closing = Some(']'),
+                '♪' | '♫' => {}
+                c => out.push(c),
+            },
+        }
+    }
+    decode_entities(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dialogue_lines_are_read_apart() {
+        let source = "1\n00:00:01,000 --> 00:00:03,000\n- Hi\n- Bye\n\n\
+                      2\n00:00:04,000 --> 00:00:05,000\n<i>One line</i>\nwrapped here\n";
+        assert_eq!(
+            to_speech(source).unwrap(),
+            "Hi.\nBye.\nOne line wrapped here."
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Pronunciation lexicons: words or patterns mapped to spellings the engine
+//! says correctly, or to phonemes for engines that accept them
+//!
+//! A lexicon file is TOML or CSV. In TOML, plain word replacements go in a
+//! `[words]` table and fuller entries in `[[entry]]` tables:
+//!
+//! ```toml
+//! [words]
+//! nginx = "engine x"
+//!
+//! [[entry]]
+//! word = "SQL"
+//! say = "sequel"
+//! phonemes = "s'i:kw@L"     # espeak
+//! ipa = "ˈsiːkwəl"          # SAPI
+//!
+//! [[entry]]
+//! regex = "v(\\d+)\\.(\\d+)"
+//! say = "version $1 point $2"
+//! language = "en"
+//! ```
+//!
+//! A CSV lexicon has a header row naming the same columns as an entry:
+//! `word`, `regex`, `say`, `phonemes`, `ipa`, `language` and `engine`.
+
+use crate::errors::{TtsError, TtsResult};
+use crate::text::{TextFilter, language_code};
+use crate::tts::{ENGINE_NAMES, PhonemeMarkup};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The layout of a TOML lexicon file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LexiconFile {
+    words: BTreeMap<String, String>,
+    entry: Vec<EntrySpec>,
+}
+
+/// One entry as written in a lexicon file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntrySpec {
+    word: Option<String>,
+    regex: Option<String>,
+    say: Option<String>,
+    phonemes: Option<String>,
+    ipa: Option<String>,
+    language: Option<String>,
+    engine: Option<String>,
+}
+
+/// What an entry matches
+#[derive(Debug, Clone)]
+enum Pattern {
+    /// A whole word, regardless of ASCII case
+    Word(String),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    pattern: Pattern,
+    /// Spelling to speak; for a regex it may refer to groups as `$1`
+    say: Option<String>,
+    /// espeak phoneme mnemonics
+    phonemes: Option<String>,
+    /// IPA, for engines that read SSML
+    ipa: Option<String>,
+    /// Only for voices of this language, e.g. "en" or "de"
+    language: Option<String>,
+    /// Only for this engine, one of [`ENGINE_NAMES`]
+    engine: Option<String>,
+}
+
+impl Entry {
+    fn word(word: String, say: String) -> Self {
+        Entry {
+            pattern: Pattern::Word(word),
+            say: Some(say),
+            phonemes: None,
+            ipa: None,
+            language: None,
+            engine: None,
+        }
+    }
+
+    /// Entries limited to an engine or a language beat general ones for the same text
+    fn specificity(&self) -> usize {
+        usize::from(self.language.is_some()) + usize::from(self.engine.is_some())
+    }
+}
+
+/// Replace words with spellings the engine pronounces correctly, e.g.
+/// "nginx" with "engine x", or with phonemes on engines that accept them
+///
+/// Words match whole and regardless of ASCII case; regexes match as written.
+/// Where matches overlap, the one starting first wins, then the longest,
+/// then the one limited to the engine or language, then the one added first.
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    entries: Vec<Entry>,
+    markup: Option<PhonemeMarkup>,
+}
+
+impl Lexicon {
+    /// A lexicon of plain word replacements
+    pub fn new<I: IntoIterator<Item = (String, String)>>(entries: I) -> Self {
+        Lexicon {
+            entries: entries
+                .into_iter()
+                .filter(|(word, _)| !word.trim().is_empty())
+                .map(|(word, say)| Entry::word(word, say))
+                .collect(),
+            markup: None,
+        }
+    }
+
+    /// Read a lexicon file: CSV when the extension is `.csv`, TOML otherwise
+    pub fn load(path: &Path) -> TtsResult<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            TtsError::FileError(format!("Cannot read lexicon '{}': {}", path.display(), e))
+        })?;
+        let invalid =
+            |e: &dyn std::fmt::Display| format!("Invalid lexicon '{}': {}", path.display(), e);
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+        let (words, specs) = if is_csv {
+            let specs = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .comment(Some(b'#'))
+                .from_reader(content.as_bytes())
+                .deserialize()
+                .collect::<Result<Vec<EntrySpec>, _>>()
+                .map_err(|e| TtsError::ConfigError(invalid(&e)))?;
+            (BTreeMap::new(), specs)
+        } else {
+            let file: LexiconFile =
+                toml::from_str(&content).map_err(|e| TtsError::ConfigError(invalid(&e)))?;
+            (file.words, file.entry)
+        };
+
+        let mut lexicon = Lexicon::new(words);
+        for (index, spec) in specs.into_iter().enumerate() {
+            let entry = Self::entry(spec).map_err(|e| {
+                TtsError::ConfigError(invalid(&format!("entry {}: {}", index + 1, e)))
+            })?;
+            lexicon.entries.push(entry);
+        }
+        Ok(lexicon)
+    }
+
+    /// Check an entry from a file and compile its regex
+    fn entry(spec: EntrySpec) -> Result<Entry, String> {
+        let pattern = match (spec.word, spec.regex) {
+            (Some(word), None) if !word.trim().is_empty() => Pattern::Word(word),
+            (None, Some(regex)) => Pattern::Regex(
+                Regex::new(&regex).map_err(|e| format!("invalid regex '{}': {}", regex, e))?,
+            ),
+            _ => return Err("give either a word or a regex".to_string()),
+        };
+        if spec.say.is_none() && spec.phonemes.is_none() && spec.ipa.is_none() {
+            return Err("give say, phonemes or ipa".to_string());
+        }
+        if let Some(engine) = &spec.engine
+            && !ENGINE_NAMES.contains(&engine.as_str())
+        {
+            return Err(format!(
+                "unknown engine '{}' (expected {})",
+                engine,
+                ENGINE_NAMES.join(", ")
+            ));
+        }
+        Ok(Entry {
+            pattern,
+            say: spec.say,
+            phonemes: spec.phonemes,
+            ipa: spec.ipa,
+            language: spec.language.map(|language| language_code(&language)),
+            engine: spec.engine,
+        })
+    }
+
+    /// Add the entries of another lexicon after these
+    pub fn extend(&mut self, other: Lexicon) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Whether some entries only apply to voices of one language
+    pub fn has_language_entries(&self) -> bool {
+        self.entries.iter().any(|entry| entry.language.is_some())
+    }
+
+    /// Keep the entries that apply to an engine and voice language, and
+    /// write pronunciations in the markup the engine understands
+    ///
+    /// Without a language, entries limited to a language are dropped.
+    pub fn for_engine(
+        mut self,
+        engine: &str,
+        markup: Option<PhonemeMarkup>,
+        language: Option<&str>,
+    ) -> Self {
+        // SAPI reports names such as "English (United States)" rather than codes
+        let language = language.map(language_code);
+        self.entries.retain(|entry| {
+            entry.engine.as_deref().is_none_or(|name| name == engine)
+                && entry.language.as_deref().is_none_or(|wanted| {
+                    language.as_deref().is_some_and(|voice| {
+                        voice == wanted
+                            || voice
+                                .strip_prefix(wanted)
+                                .is_some_and(|rest| rest.starts_with('-'))
+                    })
+                })
+        });
+        self.markup = markup;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// What to say for a match, or `None` to leave it as it is
+    fn replacement(
+        &self,
+        entry: &Entry,
+        matched: &str,
+        captures: Option<&Captures>,
+    ) -> Option<String> {
+        let phonemes = match self.markup {
+            Some(PhonemeMarkup::Espeak) => entry.phonemes.as_deref(),
+            Some(PhonemeMarkup::Ssml) => entry.ipa.as_deref(),
+            None => None,
+        };
+        if let (Some(markup), Some(phonemes)) = (self.markup, phonemes) {
+            return Some(markup.render(phonemes, matched));
+        }
+        let say = entry.say.as_deref()?;
+        Some(match captures {
+            Some(captures) => {
+                let mut expanded = String::new();
+                captures.expand(say, &mut expanded);
+                expanded
+            }
+            None => say.to_string(),
+        })
+    }
+
+    /// Matches of whole words, the longest entry at each word start
+    fn word_matches(&self, text: &str, matches: &mut Vec<Match>) {
+        let mut words: Vec<(usize, &Entry)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| matches!(entry.pattern, Pattern::Word(_)))
+            .collect();
+        words.sort_by_key(|(order, entry)| {
+            let Pattern::Word(word) = &entry.pattern else {
+                unreachable!()
+            };
+            (
+                std::cmp::Reverse(word.len()),
+                std::cmp::Reverse(entry.specificity()),
+                *order,
+            )
+        });
+        if words.is_empty() {
+            return;
+        }
+
+        let mut previous = None;
+        for (index, c) in text.char_indices() {
+            let at_word_start = previous.is_none_or(|c| !is_word_char(c));
+            previous = Some(c);
+            if !at_word_start {
+                continue;
+            }
+            for &(order, entry) in &words {
+                let Pattern::Word(word) = &entry.pattern else {
+                    continue;
+                };
+                let end = index + word.len();
+                let found = text
+                    .get(index..end)
+                    .is_some_and(|candidate| candidate.eq_ignore_ascii_case(word))
+                    && text[end..].chars().next().is_none_or(|c| !is_word_char(c));
+                // An entry with nothing to say on this engine leaves the word
+                // to the next entry that matches it
+                if found && let Some(replacement) = self.replacement(entry, &text[index..end], None)
+                {
+                    matches.push(Match {
+                        start: index,
+                        end,
+                        specificity: entry.specificity(),
+                        order,
+                        replacement,
+                    });
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// A stretch of text to replace
+struct Match {
+    start: usize,
+    end: usize,
+    specificity: usize,
+    /// Position of the entry in the lexicon
+    order: usize,
+    replacement: String,
+}
+
+impl TextFilter for Lexicon {
+    fn name(&self) -> &str {
+        "lexicon"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        if self.entries.is_empty() {
+            return text.to_string();
+        }
+
+        let mut matches = Vec::new();
+        self.word_matches(text, &mut matches);
+        for (order, entry) in self.entries.iter().enumerate() {
+            let Pattern::Regex(regex) = &entry.pattern else {
+                continue;
+            };
+            for captures in regex.captures_iter(text) {
+                let Some(found) = captures.get(0).filter(|found| !found.is_empty()) else {
+                    continue;
+                };
+                if let Some(replacement) = self.replacement(entry, found.as_str(), Some(&captures))
+                {
+                    matches.push(Match {
+                        start: found.start(),
+                        end: found.end(),
+                        specificity: entry.specificity(),
+                        order,
+                        replacement,
+                    });
+                }
+            }
+        }
+        matches.sort_by_key(|m| {
+            (
+                m.start,
+                std::cmp::Reverse(m.end),
+                std::cmp::Reverse(m.specificity),
+                m.order,
+            )
+        });
+
+        let mut result = String::with_capacity(text.len());
+        let mut index = 0;
+        for m in matches {
+            // Skip matches that overlap one already replaced
+            if m.start < index {
+                continue;
+            }
+            result.push_str(&text[index..m.start]);
+            result.push_str(&m.replacement);
+            index = m.end;
+        }
+        result.push_str(&text[index..]);
+        result
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english_only(word: &str, say: &str) -> Entry {
+        Lexicon::entry(EntrySpec {
+            word: Some(word.to_string()),
+            regex: None,
+            say: Some(say.to_string()),
+            phonemes: None,
+            ipa: None,
+            language: Some("en".to_string()),
+            engine: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn language_entries_match_voice_language_names() {
+        let cases = [
+            ("en-US", "Say engine x."),
+            ("en_GB", "Say engine x."),
+            ("English (United States)", "Say engine x."),
+            ("English", "Say engine x."),
+            ("German (Germany)", "Say nginx."),
+            ("de", "Say nginx."),
+        ];
+        for (language, expected) in cases {
+            let mut lexicon = Lexicon::new(Vec::new());
+            lexicon.entries.push(english_only("nginx", "engine x"));
+            let lexicon = lexicon.for_engine("sapi", None, Some(language));
+            assert_eq!(
+                lexicon.apply("Say nginx."),
+                expected,
+                "language: {:?}",
+                language
+            );
+        }
+    }
+}
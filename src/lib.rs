@@ -0,0 +1,51 @@
+//! Text-to-Voice (T2V) library
+//!
+//! Cross-platform text-to-speech built on the speech engines that ship with
+//! the operating system: `say` on macOS, SAPI through PowerShell on Windows
+//! and `espeak` on Linux. The `text_to_voice` binary is a thin command-line
+//! front end over this crate, and other Rust programs can use the same API.
+//!
+//! ```no_run
+//! use text_to_voice::{create_tts_engine, text, SpeechConfig};
+//!
+//! let engine = create_tts_engine();
+//! let config = SpeechConfig::default();
+//!
+//! // Speak through the system audio output
+//! let message = text::prepare_text("Build   finished\r\nwith no errors.");
+//! engine.speak(&message, &config)?;
+//!
+//! // Or render the same speech to a WAV file
+//! engine.render_wav(&message, &config, "build.wav".as_ref())?;
+//!
+//! // Voices installed on this system
+//! println!("{}", engine.list_voices()?);
+//! # Ok::<(), text_to_voice::TtsError>(())
+//! ```
+//!
+//! Non-Rust programs can use the C ABI in [`ffi`], declared in `include/t2v.h`.
+//!
+//! The document readers in `formats` and subtitle dubbing in `dub` sit behind
+//! the default `formats` feature; without it the crate is only the engines
+//! and the text filters.
+
+pub mod abbreviations;
+#[cfg(feature = "formats")]
+pub mod dub;
+pub mod encoding;
+pub mod errors;
+pub mod ffi;
+#[cfg(feature = "formats")]
+pub mod formats;
+pub mod lexicon;
+pub mod numbers;
+#[cfg(feature = "formats")]
+mod temp;
+pub mod text;
+pub mod tts;
+
+pub use errors::{TtsError, TtsResult};
+pub use tts::{
+    ENGINE_NAMES, LinuxTts, MacOsTts, PhonemeMarkup, SpeechConfig, TextToSpeech, Voice, WindowsTts,
+    create_tts_engine,
+};
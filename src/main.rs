@@ -1,16 +1,22 @@
 use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
 use std::process;
+use std::time::Instant;
 
 // Import our custom modules
 mod config;
-mod errors;
+mod follow;
+mod queue;
 mod toml_extract;
-mod tts;
 mod ui;
 
-use config::AppConfig;
-use errors::TtsError;
-use tts::create_tts_engine;
+use config::{AppConfig, STDIN_PATH};
+use text_to_voice::encoding::{self, Encoding};
+use text_to_voice::formats::code::Language;
+use text_to_voice::formats::epub::EpubBook;
+use text_to_voice::formats::{self, InputFormat, subtitles};
+use text_to_voice::{TextToSpeech, TtsError, TtsResult, create_tts_engine};
 use ui::{
     print_config, print_error, print_info, print_stats, print_success, print_voice_header,
     show_banner, show_comprehensive_help,
@@ -31,7 +37,7 @@ fn run_app() -> Result<(), TtsError> {
     toml_extract::main();
 
     // Parse configuration from command-line arguments
-    let config = AppConfig::from_args()?;
+    let mut config = AppConfig::from_args()?;
 
     // Handle special modes first
     if config.list_voices {
@@ -43,8 +49,14 @@ fn run_app() -> Result<(), TtsError> {
         return Ok(());
     }
 
+    if config.list_chapters {
+        return handle_list_chapters(&config);
+    }
+
     // Main text-to-speech workflow
-    read_and_speak_file(&config)
+    let tts_engine = create_tts_engine();
+    config.load_text_filters(tts_engine.as_ref())?;
+    read_and_speak_file(&config, tts_engine.as_ref())
 }
 
 /// Handle voice listing mode
@@ -56,33 +68,272 @@ fn handle_list_voices() -> Result<(), TtsError> {
     Ok(())
 }
 
-/// Read a file and convert it to speech
-fn read_and_speak_file(config: &AppConfig) -> Result<(), TtsError> {
-    // Read the text file
-    print_info(&format!("Reading file: {}", config.file_path));
-    let text_content = fs::read_to_string(&config.file_path).map_err(|e| {
-        TtsError::FileError(format!("Cannot read file '{}': {}", config.file_path, e))
-    })?;
+/// Handle chapter listing mode for EPUB books
+fn handle_list_chapters(config: &AppConfig) -> Result<(), TtsError> {
+    for path in queue::build_queue(&config.file_paths, config.sort_order)? {
+        let format = config
+            .format
+            .unwrap_or_else(|| InputFormat::from_path(&path));
+        if format != InputFormat::Epub {
+            return Err(TtsError::ConfigError(format!(
+                "--list-chapters only works with EPUB books, not '{}'",
+                path.display()
+            )));
+        }
+
+        let bytes = fs::read(&path).map_err(|e| {
+            TtsError::FileError(format!("Cannot read file '{}': {}", path.display(), e))
+        })?;
+        let book = EpubBook::open(bytes)?;
+
+        print_info(&format!(
+            "Chapters in {}",
+            book.title.as_deref().unwrap_or(&path.display().to_string())
+        ));
+        for (index, chapter) in book.chapters.iter().enumerate() {
+            println!("\t {:>3}. {}", index + 1, chapter.title);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Read the queued files and convert them to speech
+fn read_and_speak_file(config: &AppConfig, tts_engine: &dyn TextToSpeech) -> Result<(), TtsError> {
+    if config.incremental {
+        return speak_stdin_lines(config, tts_engine);
+    }
+
+    let queue = queue::build_queue(&config.file_paths, config.sort_order)?;
+    if queue.len() > 1 && config.output_path.is_some() {
+        return Err(TtsError::ConfigError(
+            "--output needs a single input file".to_string(),
+        ));
+    }
+    if config.follow && (queue.len() > 1 || queue[0] == Path::new(STDIN_PATH)) {
+        return Err(TtsError::ConfigError(
+            "--follow needs a single input file".to_string(),
+        ));
+    }
+
+    // Show configuration
+    if let Some(path) = &config.config_path {
+        print_config(&format!("Using config file: {}", path.display()));
+        print_config(&format!(
+            "Text filters: {}",
+            config.pipeline.names().join(", ")
+        ));
+    }
+    for path in &config.lexicon_paths {
+        print_config(&format!("Using lexicon: {}", path.display()));
+    }
+    if let Some(voice) = &config.speech_config.voice {
+        print_config(&format!("Using voice: {}", voice));
+    }
+    if let Some(rate) = config.speech_config.rate {
+        print_config(&format!("Speaking rate: {} words per minute", rate));
+    }
+    if queue.len() > 1 {
+        print_info(&format!("Queued {} files", queue.len()));
+    }
+
+    let started = Instant::now();
+    let mut total_chars = 0;
+
+    for (index, path) in queue.iter().enumerate() {
+        if queue.len() > 1 {
+            print_info(&format!("File {} of {}", index + 1, queue.len()));
+        }
+        let stats = speak_file(path, tts_engine, config)?;
+        total_chars += stats.characters;
+
+        if config.follow {
+            let Some(encoding) = stats.encoding else {
+                return Err(TtsError::ConfigError(
+                    "--follow only works with text files".to_string(),
+                ));
+            };
+            return follow::follow_file(
+                path,
+                stats.bytes_read,
+                encoding,
+                tts_engine,
+                &config.pipeline,
+                &config.speech_config,
+            );
+        }
+    }
+
+    if queue.len() > 1 {
+        print_stats(&format!(
+            "Total: {} files, {} characters in {:.1}s",
+            queue.len(),
+            total_chars,
+            started.elapsed().as_secs_f64()
+        ));
+    }
+    Ok(())
+}
+
+/// What was read from one input
+struct FileStats {
+    /// Length of the prepared text in characters
+    characters: usize,
+    /// Raw bytes read, which is where follow mode picks up
+    bytes_read: u64,
+    /// Encoding the bytes were decoded with, for text formats
+    encoding: Option<&'static Encoding>,
+}
+
+/// Read one file (or standard input) and speak it
+fn speak_file(
+    path: &Path,
+    tts_engine: &dyn TextToSpeech,
+    config: &AppConfig,
+) -> TtsResult<FileStats> {
+    let started = Instant::now();
+
+    // Read the text file, or standard input for "-"
+    let mut path = path;
+    let mut raw_bytes = Vec::new();
+    if path == Path::new(STDIN_PATH) {
+        print_info("Reading from standard input");
+        io::stdin()
+            .read_to_end(&mut raw_bytes)
+            .map_err(|e| TtsError::FileError(format!("Cannot read standard input: {}", e)))?;
+        // Standard input that is not a terminal but not a pipe either, as
+        // under cron or from /dev/null, leaves the default file to read
+        if raw_bytes.is_empty() {
+            let Some(fallback) = &config.stdin_fallback else {
+                return Err(TtsError::FileError("Standard input is empty".to_string()));
+            };
+            print_info(&format!(
+                "Standard input is empty, reading {} instead",
+                fallback
+            ));
+            path = Path::new(fallback);
+        }
+    }
+    if path != Path::new(STDIN_PATH) {
+        print_info(&format!("Reading file: {}", path.display()));
+        raw_bytes = fs::read(path).map_err(|e| {
+            TtsError::FileError(format!("Cannot read file '{}': {}", path.display(), e))
+        })?;
+    }
+    let format = config
+        .format
+        .unwrap_or_else(|| match InputFormat::from_path(path) {
+            InputFormat::SourceCode(language) => InputFormat::SourceCode(language),
+            _ if config.force_code => InputFormat::SourceCode(Language::Generic),
+            detected => detected,
+        });
+    let document = formats::load(format, &raw_bytes, &config.read_options)?;
+    let text_content = config.pipeline.run(&document.text);
+    // Engines are never handed empty text; a followed file may still fill up
+    if text_content.trim().is_empty() && !config.follow {
+        return Err(TtsError::FileError(format!(
+            "No text to read in '{}'",
+            path.display()
+        )));
+    }
 
     print_success("File loaded successfully!");
     print_stats(&format!(
         "Content length: {} characters",
         text_content.len()
     ));
+    print_stats(&format!("Format: {}", format.name()));
+    for detail in &document.stats {
+        print_stats(detail);
+    }
 
-    // Show configuration
+    if config.announce {
+        let name = path
+            .file_name()
+            .map_or_else(|| "standard input".into(), |name| name.to_string_lossy());
+        tts_engine.speak(&format!("Reading {}.", name), &config.speech_config)?;
+    }
+
+    // Convert to speech, or render to a WAV file when requested
     print_info("Converting text to speech...");
-    if let Some(voice) = &config.speech_config.voice {
-        print_config(&format!("Using voice: {}", voice));
+    if let Some(output_path) = &config.output_path
+        && format == InputFormat::Subtitles
+    {
+        print_config(&format!("Writing timed audio to: {}", output_path));
+        let decoded = encoding::decode(&raw_bytes, config.read_options.encoding);
+        let mut cues = subtitles::parse(&decoded.text)?;
+        for cue in &mut cues {
+            cue.text = config.pipeline.run(&cue.text);
+        }
+        let report = text_to_voice::dub::render_cues(
+            tts_engine,
+            &cues,
+            &config.speech_config,
+            Path::new(output_path),
+        )?;
+        print_success(&format!("Audio saved to {}", output_path));
+        print_stats(&format!(
+            "Placed {} cues in {:.1}s of audio",
+            report.cues,
+            report.duration.as_secs_f64()
+        ));
+        if report.sped_up > 0 {
+            print_stats(&format!(
+                "Sped up {} cues to fit their slots (up to {} wpm)",
+                report.sped_up, report.fastest_rate
+            ));
+        }
+        if report.overrunning > 0 {
+            print_stats(&format!(
+                "{} cues are still too long and overlap the next cue",
+                report.overrunning
+            ));
+        }
+    } else if let Some(output_path) = &config.output_path {
+        print_config(&format!("Writing audio to: {}", output_path));
+        tts_engine.render_wav(&text_content, &config.speech_config, Path::new(output_path))?;
+        print_success(&format!("Audio saved to {}", output_path));
+    } else if text_content.trim().is_empty() {
+        print_info("Nothing to read yet, waiting for new lines");
+    } else {
+        tts_engine.speak(&text_content, &config.speech_config)?;
+        print_success("Text-to-speech completed successfully!");
     }
-    if let Some(rate) = config.speech_config.rate {
-        print_config(&format!("Speaking rate: {} words per minute", rate));
+
+    // Feed items count as heard only once they were spoken or saved
+    if let Some(heard) = &config.read_options.heard {
+        formats::feed::remember_heard(heard, &document.item_ids)?;
     }
 
-    // Convert to speech
-    let tts_engine = create_tts_engine();
-    tts_engine.speak(&text_content, &config.speech_config)?;
+    print_stats(&format!(
+        "Finished {} in {:.1}s",
+        path.display(),
+        started.elapsed().as_secs_f64()
+    ));
+    Ok(FileStats {
+        characters: text_content.len(),
+        bytes_read: raw_bytes.len() as u64,
+        encoding: document.encoding,
+    })
+}
+
+/// Speak standard input line by line as it arrives, until EOF
+fn speak_stdin_lines(config: &AppConfig, tts_engine: &dyn TextToSpeech) -> Result<(), TtsError> {
+    print_info("Speaking standard input line by line (Ctrl+D to finish)...");
+    let mut lines_spoken = 0;
+
+    for line in io::stdin().lock().lines() {
+        let line =
+            line.map_err(|e| TtsError::FileError(format!("Cannot read standard input: {}", e)))?;
+        let line = config.pipeline.run(&line);
+        if line.is_empty() {
+            continue;
+        }
+        tts_engine.speak(&line, &config.speech_config)?;
+        lines_spoken += 1;
+    }
 
+    print_stats(&format!("Lines spoken: {}", lines_spoken));
     print_success("Text-to-speech completed successfully!");
     Ok(())
 }
@@ -0,0 +1,1436 @@
+//! Numbers, dates, times, money and units written out as words, so every
+//! engine reads them the same way
+//!
+//! The words follow a [`Locale`]: American and British English differ in
+//! date order, "and" inside numbers and unit spellings, and German text
+//! swaps the decimal point and the thousands separator.
+
+use crate::text::{Locale, TextFilter, outside_markup};
+use regex::{Captures, Regex};
+
+/// Write numbers, dates, times, money amounts, units, ordinals and Roman
+/// numerals in headings as words
+///
+/// Without a locale, text is left for the engine to read as it likes.
+#[derive(Debug, Clone)]
+pub struct ExpandNumbers {
+    locale: Option<Locale>,
+    /// A run of digits and the separators between them
+    token: Regex,
+    /// Roman numerals after words such as "Chapter"
+    titled_roman: Regex,
+    /// Roman numerals numbering a heading or a line, as in "IV. Results"
+    leading_roman: Regex,
+    /// Phone numbers, which are left for the engine to read digit by digit
+    phone: Regex,
+}
+
+/// A numeric token, recognized by its shape
+enum Value<'a> {
+    /// Digits, with any thousands separators removed
+    Integer(String),
+    /// Whole and fractional digits
+    Decimal(String, &'a str),
+    Date {
+        year: u64,
+        month: usize,
+        day: u64,
+    },
+    Time {
+        hour: u64,
+        minute: u64,
+    },
+    Range(u64, u64),
+}
+
+/// A unit symbol and its words: English singular and plural (British
+/// spelling where it differs), German singular and plural, and whether the
+/// German word is feminine
+struct Unit {
+    symbol: &'static str,
+    english: (&'static str, &'static str),
+    british: Option<(&'static str, &'static str)>,
+    german: (&'static str, &'static str),
+    feminine: bool,
+}
+
+const fn unit(
+    symbol: &'static str,
+    english: (&'static str, &'static str),
+    german: (&'static str, &'static str),
+) -> Unit {
+    Unit {
+        symbol,
+        english,
+        british: None,
+        german,
+        feminine: false,
+    }
+}
+
+/// Units, longest symbol first so "km/h" is not read as "km"
+static UNITS: [Unit; 26] = [
+    unit(
+        "km/h",
+        ("kilometer per hour", "kilometers per hour"),
+        ("Kilometer pro Stunde", "Kilometer pro Stunde"),
+    )
+    .british(("kilometre per hour", "kilometres per hour")),
+    unit(
+        "kWh",
+        ("kilowatt hour", "kilowatt hours"),
+        ("Kilowattstunde", "Kilowattstunden"),
+    )
+    .feminine(),
+    unit(
+        "mph",
+        ("mile per hour", "miles per hour"),
+        ("Meile pro Stunde", "Meilen pro Stunde"),
+    )
+    .feminine(),
+    unit(
+        "GHz",
+        ("gigahertz", "gigahertz"),
+        ("Gigahertz", "Gigahertz"),
+    ),
+    unit(
+        "MHz",
+        ("megahertz", "megahertz"),
+        ("Megahertz", "Megahertz"),
+    ),
+    unit(
+        "kHz",
+        ("kilohertz", "kilohertz"),
+        ("Kilohertz", "Kilohertz"),
+    ),
+    unit("min", ("minute", "minutes"), ("Minute", "Minuten")).feminine(),
+    unit(
+        "°C",
+        ("degree Celsius", "degrees Celsius"),
+        ("Grad Celsius", "Grad Celsius"),
+    ),
+    unit(
+        "°F",
+        ("degree Fahrenheit", "degrees Fahrenheit"),
+        ("Grad Fahrenheit", "Grad Fahrenheit"),
+    ),
+    unit(
+        "km",
+        ("kilometer", "kilometers"),
+        ("Kilometer", "Kilometer"),
+    )
+    .british(("kilometre", "kilometres")),
+    unit(
+        "cm",
+        ("centimeter", "centimeters"),
+        ("Zentimeter", "Zentimeter"),
+    )
+    .british(("centimetre", "centimetres")),
+    unit(
+        "mm",
+        ("millimeter", "millimeters"),
+        ("Millimeter", "Millimeter"),
+    )
+    .british(("millimetre", "millimetres")),
+    unit("kg", ("kilogram", "kilograms"), ("Kilogramm", "Kilogramm")),
+    unit(
+        "mg",
+        ("milligram", "milligrams"),
+        ("Milligramm", "Milligramm"),
+    ),
+    unit(
+        "ml",
+        ("milliliter", "milliliters"),
+        ("Milliliter", "Milliliter"),
+    )
+    .british(("millilitre", "millilitres")),
+    unit(
+        "ms",
+        ("millisecond", "milliseconds"),
+        ("Millisekunde", "Millisekunden"),
+    )
+    .feminine(),
+    unit("KB", ("kilobyte", "kilobytes"), ("Kilobyte", "Kilobyte")),
+    unit("MB", ("megabyte", "megabytes"), ("Megabyte", "Megabyte")),
+    unit("GB", ("gigabyte", "gigabytes"), ("Gigabyte", "Gigabyte")),
+    unit("TB", ("terabyte", "terabytes"), ("Terabyte", "Terabyte")),
+    unit("kW", ("kilowatt", "kilowatts"), ("Kilowatt", "Kilowatt")),
+    unit("Hz", ("hertz", "hertz"), ("Hertz", "Hertz")),
+    unit("%", ("percent", "percent"), ("Prozent", "Prozent")).british(("per cent", "per cent")),
+    unit("m", ("meter", "meters"), ("Meter", "Meter")).british(("metre", "metres")),
+    unit("s", ("second", "seconds"), ("Sekunde", "Sekunden")).feminine(),
+    unit("h", ("hour", "hours"), ("Stunde", "Stunden")).feminine(),
+];
+
+impl Unit {
+    const fn british(mut self, words: (&'static str, &'static str)) -> Self {
+        self.british = Some(words);
+        self
+    }
+
+    const fn feminine(mut self) -> Self {
+        self.feminine = true;
+        self
+    }
+
+    fn words(&self, locale: Locale) -> (&'static str, &'static str) {
+        match locale {
+            Locale::EnUs => self.english,
+            Locale::EnGb => self.british.unwrap_or(self.english),
+            Locale::De => self.german,
+        }
+    }
+}
+
+/// A currency: its symbol, its ISO code, and the words for the unit and
+/// hundredth in English and German, singular then plural
+struct Currency {
+    symbol: char,
+    code: &'static str,
+    english: [&'static str; 4],
+    german: [&'static str; 4],
+    has_cents: bool,
+}
+
+static CURRENCIES: [Currency; 4] = [
+    Currency {
+        symbol: '$',
+        code: "USD",
+        english: ["dollar", "dollars", "cent", "cents"],
+        german: ["Dollar", "Dollar", "Cent", "Cent"],
+        has_cents: true,
+    },
+    Currency {
+        symbol: '€',
+        code: "EUR",
+        english: ["euro", "euros", "cent", "cents"],
+        german: ["Euro", "Euro", "Cent", "Cent"],
+        has_cents: true,
+    },
+    Currency {
+        symbol: '£',
+        code: "GBP",
+        english: ["pound", "pounds", "penny", "pence"],
+        german: ["Pfund", "Pfund", "Penny", "Pence"],
+        has_cents: true,
+    },
+    Currency {
+        symbol: '¥',
+        code: "JPY",
+        english: ["yen", "yen", "", ""],
+        german: ["Yen", "Yen", "", ""],
+        has_cents: false,
+    },
+];
+
+/// A word after an amount of money that scales it, as in "$5M" or "3,2 Mio. €"
+struct Multiplier {
+    symbol: &'static str,
+    english: &'static str,
+    /// German for exactly one, then for more
+    german: (&'static str, &'static str),
+}
+
+const fn multiplier(
+    symbol: &'static str,
+    english: &'static str,
+    german: (&'static str, &'static str),
+) -> Multiplier {
+    Multiplier {
+        symbol,
+        english,
+        german,
+    }
+}
+
+/// Multipliers, longest symbol first
+static MULTIPLIERS: [Multiplier; 10] = [
+    multiplier("thousand", "thousand", ("eintausend", "tausend")),
+    multiplier("million", "million", ("eine Million", "Millionen")),
+    multiplier("billion", "billion", ("eine Milliarde", "Milliarden")),
+    multiplier("Mio.", "million", ("eine Million", "Millionen")),
+    multiplier("Mrd.", "billion", ("eine Milliarde", "Milliarden")),
+    multiplier("Mio", "million", ("eine Million", "Millionen")),
+    multiplier("Mrd", "billion", ("eine Milliarde", "Milliarden")),
+    multiplier("bn", "billion", ("eine Milliarde", "Milliarden")),
+    multiplier("k", "thousand", ("eintausend", "tausend")),
+    multiplier("M", "million", ("eine Million", "Millionen")),
+];
+
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const GERMAN_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+impl ExpandNumbers {
+    pub fn new(locale: Option<Locale>) -> Self {
+        // C, D and M are left out: "Appendix C" is a letter, not a hundred
+        let roman = "[IVXL]+";
+        ExpandNumbers {
+            locale,
+            token: Regex::new(r"\d(?:[\d.,:/-]*\d)?").expect("valid regex"),
+            titled_roman: Regex::new(&format!(
+                r"\b(Chapter|Part|Book|Volume|Act|Scene|Section|Appendix|Kapitel|Teil|Buch|Band|Akt|Szene|Abschnitt|Anhang) ({})\b",
+                roman
+            ))
+            .expect("valid regex"),
+            leading_roman: Regex::new(&format!(r"(?m)^(Heading: )?({})(?:([.:)]) |\.?$)", roman))
+                .expect("valid regex"),
+            // "+1 (555) 123-4567", "(555) 123-4567", "555-123-4567" and "555-1234"
+            phone: Regex::new(
+                r"\+\d[\d ().-]*\d|\(\d{2,5}\) ?\d{3,4}[ .-]\d{4}\b|\b\d{3}[.-]\d{3}[.-]\d{4}\b|\b\d{3}-\d{4}\b",
+            )
+            .expect("valid regex"),
+        }
+    }
+
+    pub fn locale(&self) -> Option<Locale> {
+        self.locale
+    }
+
+    /// Expand a stretch of text that holds no phoneme markup
+    fn expand(&self, locale: Locale, text: &str) -> String {
+        let text = self.titled_roman.replace_all(text, |captures: &Captures| {
+            match roman_value(&captures[2]) {
+                Some(value) => format!("{} {}", &captures[1], cardinal(locale, value)),
+                None => captures[0].to_string(),
+            }
+        });
+        let text = self
+            .leading_roman
+            .replace_all(&text, |captures: &Captures| {
+                let heading = captures.get(1).map_or("", |heading| heading.as_str());
+                // Outside headings, only numerals that number a line count
+                let numbering = !heading.is_empty() || captures.get(3).is_some();
+                match roman_value(&captures[2]).filter(|_| numbering) {
+                    Some(value) => {
+                        let words = capitalize(&cardinal(locale, value));
+                        match captures.get(3) {
+                            Some(punctuation) => {
+                                format!("{}{}{} ", heading, words, punctuation.as_str())
+                            }
+                            None => format!("{}{}.", heading, words),
+                        }
+                    }
+                    None => captures[0].to_string(),
+                }
+            });
+
+        // An international number needs enough digits, so "+5 points" is still read
+        let phones: Vec<_> = self
+            .phone
+            .find_iter(&text)
+            .filter(|phone| phone.as_str().bytes().filter(u8::is_ascii_digit).count() >= 7)
+            .map(|phone| phone.range())
+            .collect();
+
+        let mut result = String::with_capacity(text.len());
+        let mut index = 0;
+        for found in self.token.find_iter(&text) {
+            let in_phone = phones
+                .iter()
+                .any(|phone| found.start() < phone.end && phone.start < found.end());
+            if found.start() < index || in_phone {
+                continue;
+            }
+            // A currency sign before the number may already have been written out
+            if let Some((start, end, words)) =
+                self.number(locale, &text, found.start(), found.end())
+                && start >= index
+            {
+                result.push_str(&text[index..start]);
+                result.push_str(&words);
+                index = end;
+            }
+        }
+        result.push_str(&text[index..]);
+        result
+    }
+
+    /// Words for the number at `start..end`, with the span they replace,
+    /// which takes in currency signs, units and suffixes around it
+    fn number(
+        &self,
+        locale: Locale,
+        text: &str,
+        start: usize,
+        end: usize,
+    ) -> Option<(usize, usize, String)> {
+        let before = &text[..start];
+        let after = &text[end..];
+        // Digits inside words and codes, as in "mp3" or "H2O", are left alone
+        if before.chars().last().is_some_and(is_word_char) {
+            return None;
+        }
+        // The words for a German time end in "Uhr", so one written after it is taken in
+        let uhr = Some(" Uhr")
+            .filter(|uhr| {
+                locale == Locale::De
+                    && after
+                        .strip_prefix(uhr)
+                        .is_some_and(|rest| !starts_word(rest))
+            })
+            .map_or(0, str::len);
+        let value = match dotted_time(&text[start..end]).filter(|_| uhr > 0) {
+            Some(time) => time,
+            None => classify(locale, &text[start..end])?,
+        };
+
+        match value {
+            Value::Date { year, month, day } => {
+                Some((start, end, date(locale, before, year, month, day)))
+            }
+            Value::Time { hour, minute } => {
+                Some((start, end + uhr, time(locale, hour, minute))).filter(|_| !starts_word(after))
+            }
+            Value::Range(from, to) => {
+                if starts_word(after) {
+                    return None;
+                }
+                let read = |n: u64| {
+                    if is_year(n) && is_year(from) && is_year(to) {
+                        year(locale, n)
+                    } else {
+                        cardinal(locale, n)
+                    }
+                };
+                let to_word = if locale == Locale::De { "bis" } else { "to" };
+                Some((
+                    start,
+                    end,
+                    format!("{} {} {}", read(from), to_word, read(to)),
+                ))
+            }
+            Value::Integer(_) | Value::Decimal(..) => self.amount(locale, text, start, end, value),
+        }
+    }
+
+    /// Integers and decimals, with the money, unit, ordinal or sign around them
+    fn amount(
+        &self,
+        locale: Locale,
+        text: &str,
+        start: usize,
+        end: usize,
+        value: Value,
+    ) -> Option<(usize, usize, String)> {
+        let before = &text[..start];
+        let after = &text[end..];
+
+        // Currency sign in front, as in "$5" or "€ 12,50"
+        let trimmed = before.strip_suffix(' ').unwrap_or(before);
+        let prefix = trimmed.chars().last().and_then(|sign| {
+            CURRENCIES
+                .iter()
+                .find(|currency| currency.symbol == sign)
+                .map(|currency| (currency, trimmed.len() - sign.len_utf8()))
+        });
+        if let Some((currency, money_start)) = prefix {
+            let (multiplier, rest) = multiplier_after(after);
+            if starts_word(rest) {
+                return None;
+            }
+            let words = money(locale, currency, &value, multiplier)?;
+            return Some((money_start, end + (after.len() - rest.len()), words));
+        }
+
+        // Currency after the amount, as in "12,50 €" or "5 USD"
+        let (multiplier, rest) = multiplier_after(after);
+        let spaced = rest.strip_prefix(' ').unwrap_or(rest);
+        let suffix = CURRENCIES.iter().find_map(|currency| {
+            let length = if spaced.starts_with(currency.symbol) {
+                currency.symbol.len_utf8()
+            } else if spaced.starts_with(currency.code) {
+                currency.code.len()
+            } else {
+                return None;
+            };
+            Some((currency, length)).filter(|_| !starts_word(&spaced[length..]))
+        });
+        if let Some((currency, length)) = suffix {
+            let words = money(locale, currency, &value, multiplier)?;
+            return Some((start, end + (after.len() - spaced.len()) + length, words));
+        }
+
+        let negative = negative_sign(before);
+        let sign_start = start - negative.map_or(0, char::len_utf8);
+        let signed = |words: String| match negative {
+            Some(_) => format!("minus {}", words),
+            None => words,
+        };
+
+        if let Some((unit, length)) = unit_after(after) {
+            let (singular, plural) = unit.words(locale);
+            let one = matches!(&value, Value::Integer(digits) if digits == "1");
+            let words = if one && negative.is_none() {
+                match locale {
+                    Locale::De if unit.feminine => format!("eine {}", singular),
+                    Locale::De => format!("ein {}", singular),
+                    _ => format!("one {}", singular),
+                }
+            } else {
+                format!("{} {}", number_words(locale, &value), plural)
+            };
+            return Some((sign_start, end + length, signed(words)));
+        }
+
+        let Value::Integer(digits) = &value else {
+            return (!starts_word(after))
+                .then(|| (sign_start, end, signed(number_words(locale, &value))));
+        };
+        let n = digits
+            .parse::<u64>()
+            .ok()
+            .filter(|_| !digits.starts_with('0') || digits.len() == 1);
+
+        // English ordinals, as in "21st"
+        if locale != Locale::De
+            && let Some(n) = n
+            && let Some(suffix) = ["st", "nd", "rd", "th"]
+                .into_iter()
+                .find(|suffix| after.starts_with(suffix))
+            && !starts_word(&after[suffix.len()..])
+        {
+            return Some((
+                start,
+                end + suffix.len(),
+                english_ordinal(n, locale == Locale::EnGb),
+            ));
+        }
+        // Decades, as in "1990s"
+        if locale != Locale::De
+            && let Some(n) = n.filter(|&n| end - start == 4 && is_year(n) && n.is_multiple_of(10))
+            && let Some(rest) = after.strip_prefix('s')
+            && !starts_word(rest)
+        {
+            let words = year(locale, n);
+            let words = match words.strip_suffix('y') {
+                Some(stem) => format!("{}ies", stem),
+                None => format!("{}s", words),
+            };
+            return Some((start, end + 1, words));
+        }
+        if starts_word(after) {
+            return None;
+        }
+        // German ordinals after an article or preposition, as in "der 3. Platz"
+        if locale == Locale::De
+            && let Some(n) = n
+            && let Some(ending) = german_ending(before)
+            && after.strip_prefix(". ").is_some_and(starts_word)
+        {
+            return Some((start, end + 1, german_ordinal(n, ending)));
+        }
+
+        // Days next to a month, as in "October 18", "18 October" or "18. Oktober"
+        if let Some(n) = n.filter(|n| (1..=31).contains(n)) {
+            if locale == Locale::De
+                && let Some(month) = after.strip_prefix(". ")
+                && GERMAN_MONTHS.iter().any(|name| month.starts_with(name))
+            {
+                let ending = german_ending(before).unwrap_or("n");
+                return Some((start, end + 1, german_ordinal(n, ending)));
+            }
+            let month_before = before
+                .trim_end()
+                .rsplit(|c: char| !c.is_alphabetic() && c != '.')
+                .next()
+                .unwrap_or_default();
+            if locale != Locale::De && is_english_month(month_before) {
+                return Some((start, end, english_ordinal(n, locale == Locale::EnGb)));
+            }
+            let month_after = after
+                .strip_prefix(' ')
+                .and_then(|rest| rest.split(|c: char| !c.is_alphabetic()).next())
+                .unwrap_or_default();
+            if locale != Locale::De && is_english_month(month_after) {
+                let ordinal = english_ordinal(n, locale == Locale::EnGb);
+                return Some((start, end, format!("the {} of", ordinal)));
+            }
+        }
+
+        // A year is written without a separator; "1,234" is a count
+        let words = match n {
+            Some(n) if negative.is_none() && end - start == 4 && is_year(n) => year(locale, n),
+            _ => number_words(locale, &value),
+        };
+        Some((sign_start, end, signed(words)))
+    }
+}
+
+impl TextFilter for ExpandNumbers {
+    fn name(&self) -> &str {
+        "numbers"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let Some(locale) = self.locale else {
+            return text.to_string();
+        };
+        outside_markup(text, |text| self.expand(locale, text))
+    }
+}
+
+/// Recognize a token by its shape in the locale
+fn classify(locale: Locale, token: &str) -> Option<Value<'_>> {
+    let (group, point) = match locale {
+        Locale::De => ('.', ','),
+        _ => (',', '.'),
+    };
+    let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let parts: Vec<&str> = token.split(['-', '/', '.', ':', ',']).collect();
+    let number = |part: &str| part.parse::<u64>().ok();
+
+    // 2026-10-18
+    if let [year, month, day] = token.split('-').collect::<Vec<_>>()[..]
+        && year.len() == 4
+        && month.len() == 2
+        && day.len() == 2
+        && parts.len() == 3
+    {
+        return date_value(number(year)?, number(month)?, number(day)?);
+    }
+    // 10/18/2026 in the US, 18/10/2026 elsewhere, and 18.10.2026
+    for separator in ['/', '.'] {
+        if let [first, second, year] = token.split(separator).collect::<Vec<_>>()[..]
+            && parts.len() == 3
+            && year.len() == 4
+            && first.len() <= 2
+            && second.len() <= 2
+        {
+            let (first, second, year) = (number(first)?, number(second)?, number(year)?);
+            return if separator == '/' && locale == Locale::EnUs {
+                date_value(year, first, second)
+            } else {
+                date_value(year, second, first)
+            };
+        }
+    }
+    // 14:05
+    if let [hour, minute] = token.split(':').collect::<Vec<_>>()[..]
+        && parts.len() == 2
+        && minute.len() == 2
+    {
+        let (hour, minute) = (number(hour)?, number(minute)?);
+        return (hour < 24 && minute < 60).then_some(Value::Time { hour, minute });
+    }
+    // 10-20; local phone numbers such as 555-1234 are not ranges
+    if let [from, to] = token.split('-').collect::<Vec<_>>()[..]
+        && parts.len() == 2
+        && to.len() <= 4
+        && from.len() <= to.len()
+        && !(from.len() == 3 && to.len() == 4)
+    {
+        let (from, to) = (number(from)?, number(to)?);
+        return (from < to).then_some(Value::Range(from, to));
+    }
+
+    // 1,234.5 or 1.234,5 in German
+    let (whole, fraction) = match token.split_once(point) {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (token, None),
+    };
+    if fraction.is_some_and(|fraction| !all_digits(fraction)) {
+        return None;
+    }
+    let groups: Vec<&str> = whole.split(group).collect();
+    let grouped = groups.len() > 1
+        && (1..=3).contains(&groups[0].len())
+        && groups[1..].iter().all(|group| group.len() == 3);
+    if !groups.iter().all(|group| all_digits(group)) || (groups.len() > 1 && !grouped) {
+        return None;
+    }
+    let digits = groups.concat();
+    // Grouping says the digits make one number, so it is not read digit by
+    // digit; one too large to say is left as written
+    if grouped && digits.len() > 15 {
+        return None;
+    }
+    match fraction {
+        Some(fraction) => Some(Value::Decimal(digits, fraction)),
+        None => Some(Value::Integer(digits)),
+    }
+}
+
+fn date_value<'a>(year: u64, month: u64, day: u64) -> Option<Value<'a>> {
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&day).then_some(Value::Date {
+        year,
+        month: month as usize,
+        day,
+    })
+}
+
+/// A German time written with a point, as in "10.30 Uhr"
+fn dotted_time<'a>(token: &str) -> Option<Value<'a>> {
+    let (hour, minute) = token.split_once('.')?;
+    if hour.len() > 2 || minute.len() != 2 {
+        return None;
+    }
+    let (hour, minute) = (hour.parse::<u64>().ok()?, minute.parse::<u64>().ok()?);
+    (hour < 24 && minute < 60).then_some(Value::Time { hour, minute })
+}
+
+/// Words for an integer or decimal
+fn number_words(locale: Locale, value: &Value) -> String {
+    match value {
+        Value::Integer(digits) => integer_words(locale, digits),
+        Value::Decimal(whole, fraction) => {
+            let point = if locale == Locale::De {
+                "Komma"
+            } else {
+                "point"
+            };
+            format!(
+                "{} {} {}",
+                integer_words(locale, whole),
+                point,
+                digit_words(locale, fraction)
+            )
+        }
+        _ => String::new(),
+    }
+}
+
+/// An integer as a number, or digit by digit when it is too long or starts
+/// with zero, like an account or phone number
+fn integer_words(locale: Locale, digits: &str) -> String {
+    match digits.parse::<u64>() {
+        Ok(n) if digits.len() <= 15 && (!digits.starts_with('0') || digits.len() == 1) => {
+            cardinal(locale, n)
+        }
+        _ => digit_words(locale, digits),
+    }
+}
+
+fn digit_words(locale: Locale, digits: &str) -> String {
+    digits
+        .bytes()
+        .map(|digit| {
+            let digit = usize::from(digit - b'0');
+            match locale {
+                Locale::De => GERMAN_ONES[digit],
+                _ => ENGLISH_ONES[digit],
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An amount of money, as in "five dollars and fifty cents" or "fünf Millionen Euro"
+fn money(
+    locale: Locale,
+    currency: &Currency,
+    value: &Value,
+    multiplier: Option<&Multiplier>,
+) -> Option<String> {
+    let names = match locale {
+        Locale::De => currency.german,
+        _ => currency.english,
+    };
+    let one = matches!(value, Value::Integer(digits) if digits == "1");
+    if let Some(multiplier) = multiplier {
+        return Some(match locale {
+            Locale::De if one => format!("{} {}", multiplier.german.0, names[1]),
+            Locale::De => format!(
+                "{} {} {}",
+                number_words(locale, value),
+                multiplier.german.1,
+                names[1]
+            ),
+            _ => format!(
+                "{} {} {}",
+                number_words(locale, value),
+                multiplier.english,
+                names[1]
+            ),
+        });
+    }
+
+    let (whole, cents) = match value {
+        Value::Integer(digits) => (digits.as_str(), None),
+        Value::Decimal(whole, fraction) if fraction.len() == 2 && currency.has_cents => {
+            (whole.as_str(), fraction.parse::<u64>().ok())
+        }
+        value => return Some(format!("{} {}", number_words(locale, value), names[1])),
+    };
+    let units = whole.parse::<u64>().ok()?;
+    let count = |n: u64, singular: &str, plural: &str| match (locale, n) {
+        (Locale::De, 1) => format!("ein {}", singular),
+        (_, 1) => format!("one {}", singular),
+        (_, n) => format!("{} {}", cardinal(locale, n), plural),
+    };
+    Some(match cents.filter(|&cents| cents > 0) {
+        None => count(units, names[0], names[1]),
+        Some(cents) if units == 0 => count(cents, names[2], names[3]),
+        Some(cents) if locale == Locale::De => {
+            format!(
+                "{} {}",
+                count(units, names[0], names[1]),
+                cardinal(locale, cents)
+            )
+        }
+        Some(cents) => format!(
+            "{} and {}",
+            count(units, names[0], names[1]),
+            count(cents, names[2], names[3])
+        ),
+    })
+}
+
+/// A multiplier right after an amount, with the text after it
+fn multiplier_after(after: &str) -> (Option<&'static Multiplier>, &str) {
+    let spaced = after.strip_prefix(' ').unwrap_or(after);
+    for multiplier in &MULTIPLIERS {
+        // Single letters must touch the number, as in "5M"
+        let text = if multiplier.symbol.len() == 1 {
+            after
+        } else {
+            spaced
+        };
+        if let Some(rest) = text.strip_prefix(multiplier.symbol)
+            && (multiplier.symbol.ends_with('.') || !starts_word(rest))
+        {
+            return (Some(multiplier), rest);
+        }
+    }
+    (None, after)
+}
+
+/// A unit right after a number, and the length taken by it and the space before it
+fn unit_after(after: &str) -> Option<(&'static Unit, usize)> {
+    let spaced = after.strip_prefix(' ');
+    UNITS.iter().find_map(|unit| {
+        // Single letters need the space, so "1990s" is not read as seconds
+        let (text, space) = match spaced {
+            Some(spaced) => (spaced, 1),
+            None if unit.symbol.len() > 1 || unit.symbol == "%" => (after, 0),
+            None => return None,
+        };
+        let rest = text.strip_prefix(unit.symbol)?;
+        (!starts_word(rest) || unit.symbol == "%").then_some((unit, space + unit.symbol.len()))
+    })
+}
+
+/// A minus sign right before a number that starts a word
+fn negative_sign(before: &str) -> Option<char> {
+    let mut chars = before.chars().rev();
+    let sign = chars.next().filter(|&c| c == '-' || c == '−')?;
+    chars
+        .next()
+        .is_none_or(|c| c.is_whitespace() || c == '(')
+        .then_some(sign)
+}
+
+fn starts_word(text: &str) -> bool {
+    text.chars().next().is_some_and(is_word_char)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_year(n: u64) -> bool {
+    (1100..=2099).contains(&n)
+}
+
+fn is_english_month(word: &str) -> bool {
+    let word = word.trim_end_matches('.');
+    word.len() >= 3
+        && ENGLISH_MONTHS
+            .iter()
+            .any(|month| month.starts_with(word) && (word.len() == 3 || word.len() == month.len()))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The value of a Roman numeral written the standard way, such as "XIV"
+fn roman_value(numeral: &str) -> Option<u64> {
+    let digit = |c: char| match c {
+        'I' => 1,
+        'V' => 5,
+        'X' => 10,
+        'L' => 50,
+        'C' => 100,
+        'D' => 500,
+        'M' => 1000,
+        _ => 0,
+    };
+    let digits: Vec<u64> = numeral.chars().map(digit).collect();
+    let value = digits
+        .iter()
+        .enumerate()
+        .map(|(index, &d)| {
+            if digits.get(index + 1).is_some_and(|&next| next > d) {
+                -(d as i64)
+            } else {
+                d as i64
+            }
+        })
+        .sum::<i64>();
+    // Only numerals that round-trip, so words like "MIX" or "DID" stay words
+    let value = u64::try_from(value)
+        .ok()
+        .filter(|&value| (1..4000).contains(&value))?;
+    (to_roman(value) == numeral).then_some(value)
+}
+
+fn to_roman(mut value: u64) -> String {
+    const NUMERALS: [(u64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut numeral = String::new();
+    for (step, letters) in NUMERALS {
+        while value >= step {
+            numeral.push_str(letters);
+            value -= step;
+        }
+    }
+    numeral
+}
+
+fn cardinal(locale: Locale, n: u64) -> String {
+    match locale {
+        Locale::EnUs => english_cardinal(n, false),
+        Locale::EnGb => english_cardinal(n, true),
+        Locale::De => german_cardinal(n),
+    }
+}
+
+fn year(locale: Locale, n: u64) -> String {
+    match locale {
+        Locale::De => german_year(n),
+        _ => english_year(n, locale == Locale::EnGb),
+    }
+}
+
+/// A date; `before` is the text before it, which decides the German case
+fn date(locale: Locale, before: &str, year_number: u64, month: usize, day: u64) -> String {
+    let year_words = year(locale, year_number);
+    match locale {
+        Locale::EnUs => format!(
+            "{} {}, {}",
+            ENGLISH_MONTHS[month - 1],
+            english_ordinal(day, false),
+            year_words
+        ),
+        Locale::EnGb => format!(
+            "the {} of {}, {}",
+            english_ordinal(day, true),
+            ENGLISH_MONTHS[month - 1],
+            year_words
+        ),
+        Locale::De => format!(
+            "{} {} {}",
+            german_ordinal(day, german_ending(before).unwrap_or("n")),
+            GERMAN_MONTHS[month - 1],
+            year_words
+        ),
+    }
+}
+
+fn time(locale: Locale, hour: u64, minute: u64) -> String {
+    match locale {
+        Locale::De => {
+            let hour = german_cardinal(hour);
+            let hour = hour
+                .strip_suffix("eins")
+                .map_or(hour.clone(), |stem| format!("{}ein", stem));
+            match minute {
+                0 => format!("{} Uhr", hour),
+                _ => format!("{} Uhr {}", hour, german_cardinal(minute)),
+            }
+        }
+        _ => {
+            let british = locale == Locale::EnGb;
+            let hour_words = english_cardinal(hour, british);
+            match minute {
+                0 if hour <= 12 => format!("{} o'clock", hour_words),
+                0 => format!("{} hundred", hour_words),
+                1..=9 => format!("{} oh {}", hour_words, ENGLISH_ONES[minute as usize]),
+                _ => format!("{} {}", hour_words, english_cardinal(minute, british)),
+            }
+        }
+    }
+}
+
+const ENGLISH_ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const ENGLISH_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const ENGLISH_SCALES: [&str; 7] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+fn english_below_100(n: u64) -> String {
+    let n = n as usize;
+    match n {
+        0..20 => ENGLISH_ONES[n].to_string(),
+        _ if n.is_multiple_of(10) => ENGLISH_TENS[n / 10].to_string(),
+        _ => format!("{}-{}", ENGLISH_TENS[n / 10], ENGLISH_ONES[n % 10]),
+    }
+}
+
+/// British English says "and" before the tens, as in "two hundred and five"
+fn english_below_1000(n: u64, british: bool) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    match (hundreds, rest) {
+        (0, rest) => english_below_100(rest),
+        (hundreds, 0) => format!("{} hundred", ENGLISH_ONES[hundreds as usize]),
+        (hundreds, rest) => format!(
+            "{} hundred {}{}",
+            ENGLISH_ONES[hundreds as usize],
+            if british { "and " } else { "" },
+            english_below_100(rest)
+        ),
+    }
+}
+
+fn english_cardinal(n: u64, british: bool) -> String {
+    if n == 0 {
+        return "zero".to_string();
+    }
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push(rest % 1000);
+        rest /= 1000;
+    }
+    let mut words = Vec::new();
+    for (scale, &group) in groups.iter().enumerate().rev() {
+        if group == 0 {
+            continue;
+        }
+        // "one thousand and five"
+        if scale == 0 && british && group < 100 && n >= 1000 {
+            words.push("and".to_string());
+        }
+        words.push(english_below_1000(group, british));
+        if scale > 0 {
+            words.push(ENGLISH_SCALES[scale].to_string());
+        }
+    }
+    words.join(" ")
+}
+
+fn english_ordinal(n: u64, british: bool) -> String {
+    let cardinal = english_cardinal(n, british);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |index| index + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word => match word.strip_suffix('y') {
+            Some(stem) => format!("{}ieth", stem),
+            None => format!("{}th", word),
+        },
+    };
+    format!("{}{}", head, last)
+}
+
+/// Years are read in pairs, as in "nineteen ninety-nine" and "twenty
+/// twenty-six", except "two thousand" to "two thousand nine"
+fn english_year(n: u64, british: bool) -> String {
+    if (2000..2010).contains(&n) {
+        return english_cardinal(n, british);
+    }
+    let (century, rest) = (n / 100, n % 100);
+    match rest {
+        0 => format!("{} hundred", english_below_100(century)),
+        1..=9 => format!(
+            "{} oh {}",
+            english_below_100(century),
+            ENGLISH_ONES[rest as usize]
+        ),
+        _ => format!("{} {}", english_below_100(century), english_below_100(rest)),
+    }
+}
+
+const GERMAN_ONES: [&str; 20] = [
+    "null",
+    "eins",
+    "zwei",
+    "drei",
+    "vier",
+    "fünf",
+    "sechs",
+    "sieben",
+    "acht",
+    "neun",
+    "zehn",
+    "elf",
+    "zwölf",
+    "dreizehn",
+    "vierzehn",
+    "fünfzehn",
+    "sechzehn",
+    "siebzehn",
+    "achtzehn",
+    "neunzehn",
+];
+
+const GERMAN_TENS: [&str; 10] = [
+    "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig",
+];
+
+/// Scales above a thousand, singular and plural
+const GERMAN_SCALES: [(&str, &str); 5] = [
+    ("Million", "Millionen"),
+    ("Milliarde", "Milliarden"),
+    ("Billion", "Billionen"),
+    ("Billiarde", "Billiarden"),
+    ("Trillion", "Trillionen"),
+];
+
+fn german_below_100(n: u64) -> String {
+    let n = n as usize;
+    match n {
+        0..20 => GERMAN_ONES[n].to_string(),
+        _ if n.is_multiple_of(10) => GERMAN_TENS[n / 10].to_string(),
+        _ if n % 10 == 1 => format!("einund{}", GERMAN_TENS[n / 10]),
+        _ => format!("{}und{}", GERMAN_ONES[n % 10], GERMAN_TENS[n / 10]),
+    }
+}
+
+fn german_below_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let mut words = String::new();
+    if hundreds > 0 {
+        words.push_str(if hundreds == 1 {
+            "ein"
+        } else {
+            GERMAN_ONES[hundreds as usize]
+        });
+        words.push_str("hundert");
+    }
+    if rest > 0 {
+        words.push_str(&german_below_100(rest));
+    }
+    words
+}
+
+/// "eins" becomes "ein" in front of a scale, as in "einhunderteintausend"
+fn german_prefix(n: u64) -> String {
+    let words = german_below_1000(n);
+    match words.strip_suffix("eins") {
+        Some(stem) => format!("{}ein", stem),
+        None => words,
+    }
+}
+
+/// German writes numbers below a million as one word, and larger scales apart
+fn german_cardinal(n: u64) -> String {
+    if n == 0 {
+        return "null".to_string();
+    }
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push(rest % 1000);
+        rest /= 1000;
+    }
+    let mut words = Vec::new();
+    for (scale, &group) in groups.iter().enumerate().skip(2).rev() {
+        let (singular, plural) = GERMAN_SCALES[scale - 2];
+        match group {
+            0 => {}
+            1 => words.push(format!("eine {}", singular)),
+            group => words.push(format!("{} {}", german_prefix(group), plural)),
+        }
+    }
+    let thousands = groups.get(1).copied().unwrap_or_default();
+    let mut small = String::new();
+    if thousands > 0 {
+        small.push_str(&german_prefix(thousands));
+        small.push_str("tausend");
+    }
+    if groups[0] > 0 {
+        small.push_str(&german_below_1000(groups[0]));
+    }
+    if !small.is_empty() {
+        words.push(small);
+    }
+    words.join(" ")
+}
+
+/// The ending of a German ordinal after the article or preposition before
+/// it: "der dritte" and "das dritte", but "am dritten" and "zum dritten";
+/// `None` when the word before does not decide it
+fn german_ending(before: &str) -> Option<&'static str> {
+    let word = before.split_whitespace().last()?.to_lowercase();
+    match word.as_str() {
+        "der" | "die" | "das" | "eine" => Some(""),
+        "den" | "dem" | "des" | "am" | "im" | "vom" | "zum" | "zur" | "beim" | "einen"
+        | "einem" | "einer" | "eines" => Some("n"),
+        _ => None,
+    }
+}
+
+/// A German ordinal with an ending, as in "dritte" or "dritten"
+fn german_ordinal(n: u64, ending: &str) -> String {
+    let cardinal = german_cardinal(n);
+    if !(1..20).contains(&(n % 100)) {
+        return format!("{}ste{}", cardinal, ending);
+    }
+    for (suffix, ordinal) in [
+        ("eins", "erste"),
+        ("drei", "dritte"),
+        ("sieben", "siebte"),
+        ("acht", "achte"),
+    ] {
+        if let Some(stem) = cardinal.strip_suffix(suffix) {
+            return format!("{}{}{}", stem, ordinal, ending);
+        }
+    }
+    format!("{}te{}", cardinal, ending)
+}
+
+/// Years before 2000 are read in hundreds, as in "neunzehnhundertneunundneunzig"
+fn german_year(n: u64) -> String {
+    if n >= 2000 {
+        return german_cardinal(n);
+    }
+    let (century, rest) = (n / 100, n % 100);
+    let mut words = format!("{}hundert", german_below_100(century));
+    if rest > 0 {
+        words.push_str(&german_below_100(rest));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(locale: Locale, cases: &[(&str, &str)]) {
+        let filter = ExpandNumbers::new(Some(locale));
+        for &(input, expected) in cases {
+            assert_eq!(filter.apply(input), expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn american_english() {
+        check(
+            Locale::EnUs,
+            &[
+                ("It costs $5.50.", "It costs five dollars and fifty cents."),
+                ("A $5M deal", "A five million dollars deal"),
+                (
+                    "1,234 people",
+                    "one thousand two hundred thirty-four people",
+                ),
+                (
+                    "About 1,000,000,000,000,000,000 grains",
+                    "About 1,000,000,000,000,000,000 grains",
+                ),
+                (
+                    "A debt of 1,000,000,000,000.5",
+                    "A debt of one trillion point five",
+                ),
+                ("pi is 3.14", "pi is three point one four"),
+                (
+                    "Drive at 100 km/h",
+                    "Drive at one hundred kilometers per hour",
+                ),
+                ("It was -5 °C", "It was minus five degrees Celsius"),
+                ("A 50% chance", "A fifty percent chance"),
+                ("the 21st century", "the twenty-first century"),
+                ("the 1990s", "the nineteen nineties"),
+                ("in 1999", "in nineteen ninety-nine"),
+                ("in 2005", "in two thousand five"),
+                ("On 2026-10-18", "On October eighteenth, twenty twenty-six"),
+                ("On 10/18/2026", "On October eighteenth, twenty twenty-six"),
+                ("October 18", "October eighteenth"),
+                ("18 October", "the eighteenth of October"),
+                ("At 14:05", "At fourteen oh five"),
+                ("At 9:00", "At nine o'clock"),
+                ("pages 10-20", "pages ten to twenty"),
+                ("Chapter IV", "Chapter four"),
+                ("IV. Results", "Four. Results"),
+                // Dates that do not exist are left as written
+                ("On 1999-02-30", "On 1999-02-30"),
+                ("On 2023-02-29", "On 2023-02-29"),
+                (
+                    "On 2024-02-29",
+                    "On February twenty-ninth, twenty twenty-four",
+                ),
+                ("On 2026-04-31", "On 2026-04-31"),
+                // Phone numbers, versions, addresses and codes are left alone
+                ("Call +1 (555) 123-4567 now", "Call +1 (555) 123-4567 now"),
+                ("Call (555) 123-4567", "Call (555) 123-4567"),
+                ("Call 555-123-4567", "Call 555-123-4567"),
+                ("Call 555-1234", "Call 555-1234"),
+                ("Ring +44 20 7946 0958", "Ring +44 20 7946 0958"),
+                ("version 1.2.3", "version 1.2.3"),
+                ("host 192.168.0.1", "host 192.168.0.1"),
+                ("an mp3 file", "an mp3 file"),
+                ("Appendix C", "Appendix C"),
+            ],
+        );
+    }
+
+    #[test]
+    fn british_english() {
+        check(
+            Locale::EnGb,
+            &[
+                ("205 people", "two hundred and five people"),
+                ("1,005 people", "one thousand and five people"),
+                (
+                    "On 18/10/2026",
+                    "On the eighteenth of October, twenty twenty-six",
+                ),
+                (
+                    "On 2026-10-18",
+                    "On the eighteenth of October, twenty twenty-six",
+                ),
+                ("Drive 5 km", "Drive five kilometres"),
+                ("A 50% chance", "A fifty per cent chance"),
+                ("It cost £3.20", "It cost three pounds and twenty pence"),
+                ("On 31/09/2026", "On 31/09/2026"),
+            ],
+        );
+    }
+
+    #[test]
+    fn german() {
+        check(
+            Locale::De,
+            &[
+                ("Es kostet 12,50 €", "Es kostet zwölf Euro fünfzig"),
+                ("3,2 Mio. €", "drei Komma zwei Millionen Euro"),
+                ("1.234 Leute", "eintausendzweihundertvierunddreißig Leute"),
+                (
+                    "Etwa 1.000.000.000.000.000.000 Körner",
+                    "Etwa 1.000.000.000.000.000.000 Körner",
+                ),
+                ("21 Leute", "einundzwanzig Leute"),
+                ("Es sind 1 km", "Es sind ein Kilometer"),
+                ("Es dauert 1 h", "Es dauert eine Stunde"),
+                (
+                    "Am 18.10.2026",
+                    "Am achtzehnten Oktober zweitausendsechsundzwanzig",
+                ),
+                (
+                    "Der 18.10.2026",
+                    "Der achtzehnte Oktober zweitausendsechsundzwanzig",
+                ),
+                ("Am 3. Mai", "Am dritten Mai"),
+                ("Im Jahr 1999", "Im Jahr neunzehnhundertneunundneunzig"),
+                // Times say "Uhr" once, also when written with a point
+                ("Um 10:30 Uhr", "Um zehn Uhr dreißig"),
+                ("Um 9.15 Uhr", "Um neun Uhr fünfzehn"),
+                ("Um 14:00", "Um vierzehn Uhr"),
+                ("Um 1:00 Uhr", "Um ein Uhr"),
+                // Ordinals after an article or preposition
+                ("Der 3. Platz", "Der dritte Platz"),
+                ("Das 7. Mal", "Das siebte Mal"),
+                ("Im 2. Stock", "Im zweiten Stock"),
+                ("Zum 100. Geburtstag", "Zum einhundertsten Geburtstag"),
+                ("Es waren 3. Dann", "Es waren drei. Dann"),
+                ("Seiten 10-20", "Seiten zehn bis zwanzig"),
+                ("Kapitel IV", "Kapitel vier"),
+                ("Am 30.02.2026", "Am 30.02.2026"),
+                ("Ruf +49 30 1234567 an", "Ruf +49 30 1234567 an"),
+            ],
+        );
+    }
+
+    #[test]
+    fn without_locale() {
+        let filter = ExpandNumbers::new(None);
+        assert_eq!(filter.apply("It costs $5."), "It costs $5.");
+    }
+
+    #[test]
+    fn leaves_phoneme_markup_alone() {
+        let filter = ExpandNumbers::new(Some(Locale::EnUs));
+        assert_eq!(filter.apply("[[w0n]] and 2"), "[[w0n]] and two");
+    }
+}
@@ -0,0 +1,117 @@
+use crate::config::STDIN_PATH;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use text_to_voice::{TtsError, TtsResult};
+
+/// Order for files found through a directory or glob pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Name,
+    Modified,
+}
+
+impl SortOrder {
+    /// Parse the value given to `--sort`
+    pub fn parse(value: &str) -> TtsResult<Self> {
+        match value {
+            "name" => Ok(SortOrder::Name),
+            "mtime" => Ok(SortOrder::Modified),
+            other => Err(TtsError::ConfigError(format!(
+                "Unknown sort order '{}' (expected 'name' or 'mtime')",
+                other
+            ))),
+        }
+    }
+}
+
+/// Expand the `--file` arguments into the list of files to read, in order
+///
+/// Plain paths are kept in command-line order. Directories contribute their
+/// (non-hidden) files and glob patterns their matches, sorted by `order`. A
+/// file whose name contains wildcard characters is read as it is.
+pub fn build_queue(inputs: &[String], order: SortOrder) -> TtsResult<Vec<PathBuf>> {
+    let mut queue = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+        if input == STDIN_PATH {
+            queue.push(path.to_path_buf());
+        } else if path.is_dir() {
+            let files = directory_files(path)?;
+            if files.is_empty() {
+                return Err(TtsError::FileError(format!(
+                    "Directory '{}' contains no files",
+                    input
+                )));
+            }
+            queue.extend(sorted(files, order));
+        } else if is_glob(input) && !path.exists() {
+            let files = glob_files(input)?;
+            if files.is_empty() {
+                return Err(TtsError::FileError(format!(
+                    "No files match pattern '{}'",
+                    input
+                )));
+            }
+            queue.extend(sorted(files, order));
+        } else {
+            queue.push(path.to_path_buf());
+        }
+    }
+
+    Ok(queue)
+}
+
+/// Whether an argument contains glob wildcards
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+/// Regular, non-hidden files directly inside a directory
+fn directory_files(dir: &Path) -> TtsResult<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir).map_err(|e| {
+        TtsError::FileError(format!("Cannot read directory '{}': {}", dir.display(), e))
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if path.is_file() && !hidden {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Files matching a glob pattern
+fn glob_files(pattern: &str) -> TtsResult<Vec<PathBuf>> {
+    let paths = glob::glob(pattern)
+        .map_err(|e| TtsError::ConfigError(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.map_err(|e| TtsError::FileError(e.to_string()))?;
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Sort files by name or by modification time (oldest first)
+fn sorted(mut files: Vec<PathBuf>, order: SortOrder) -> Vec<PathBuf> {
+    match order {
+        SortOrder::Name => files.sort(),
+        SortOrder::Modified => files.sort_by_cached_key(|path| {
+            let modified = fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path.clone())
+        }),
+    }
+    files
+}
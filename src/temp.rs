@@ -0,0 +1,58 @@
+//! Scratch directories for intermediate files handed to external tools
+
+use crate::errors::{TtsError, TtsResult};
+use std::collections::hash_map::RandomState;
+use std::fs::{self, DirBuilder};
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Names to try before giving up on finding a free one
+const ATTEMPTS: usize = 16;
+
+/// A temporary directory removed when dropped
+pub(crate) struct TempDir {
+    pub path: PathBuf,
+}
+
+impl TempDir {
+    /// Create a fresh directory named after `purpose` in the system temp directory
+    ///
+    /// The name ends in a random number, and a name that already exists is
+    /// never reused, so a directory or symlink someone else placed in a shared
+    /// temp directory is not written into or removed.
+    pub fn create(purpose: &str) -> TtsResult<Self> {
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+
+        for _ in 0..ATTEMPTS {
+            let path = std::env::temp_dir().join(format!(
+                "t2v-{}-{}-{:016x}",
+                purpose,
+                std::process::id(),
+                random()
+            ));
+            match builder.create(&path) {
+                Ok(()) => return Ok(TempDir { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(TtsError::SystemError(format!(
+            "Cannot create a temporary directory for {}",
+            purpose
+        )))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// A random number from the standard library's randomly seeded hasher keys
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
@@ -0,0 +1,409 @@
+//! Text preprocessing applied before text is handed to a speech engine
+
+use crate::errors::{TtsError, TtsResult};
+use std::fmt;
+
+pub use crate::abbreviations::ExpandAbbreviations;
+pub use crate::lexicon::Lexicon;
+pub use crate::numbers::ExpandNumbers;
+
+/// Convert Windows (`\r\n`) and old Mac (`\r`) line endings to `\n`
+pub fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Remove control characters that engines would either read aloud or choke on,
+/// keeping newlines and tabs
+pub fn strip_control_chars(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect()
+}
+
+/// Collapse runs of spaces and tabs into a single space, trim every line and
+/// keep at most one blank line between paragraphs
+pub fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !result.is_empty() {
+            result.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        result.push_str(&line);
+        blank_lines = 0;
+    }
+
+    result
+}
+
+/// Run the standard preprocessing steps on text loaded from a file
+pub fn prepare_text(text: &str) -> String {
+    collapse_whitespace(&strip_control_chars(&normalize_line_endings(text)))
+}
+
+/// Rewrite the text around espeak `[[...]]` and SSML `<phoneme>` spans,
+/// so phonemes written by the lexicon reach the engine unchanged
+pub(crate) fn outside_markup(text: &str, rewrite: impl Fn(&str) -> String) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some((start, end)) = markup_span(rest) {
+        result.push_str(&rewrite(&rest[..start]));
+        result.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    result.push_str(&rewrite(rest));
+    result
+}
+
+/// The first espeak `[[...]]` or SSML `<phoneme>` span in text
+fn markup_span(text: &str) -> Option<(usize, usize)> {
+    let espeak = text
+        .find("[[")
+        .and_then(|start| Some((start, start + text[start..].find("]]")? + 2)));
+    let ssml = text.find("<phoneme ").and_then(|start| {
+        let end = "</phoneme>";
+        Some((start, start + text[start..].find(end)? + end.len()))
+    });
+    match (espeak, ssml) {
+        (Some(a), Some(b)) => Some(if a.0 < b.0 { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// A step that rewrites text between loading it and speaking it
+///
+/// Filters run in order in a [`Pipeline`]. Implement this trait to add your
+/// own steps next to the built-in ones.
+pub trait TextFilter: Send + Sync {
+    /// Name used to order the filter in the config file
+    fn name(&self) -> &str;
+
+    fn apply(&self, text: &str) -> String;
+}
+
+/// Built-in filters, in the order used when the config file sets none
+///
+/// The lexicon runs first so its patterns see the text as written, and
+/// abbreviations before numbers so "Fig. 3" becomes "Figure three".
+pub const DEFAULT_FILTERS: [&str; 6] = [
+    "ansi",
+    "whitespace",
+    "urls",
+    "lexicon",
+    "abbreviations",
+    "numbers",
+];
+
+/// Language conventions for writing numbers and dates as words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    /// "October eighteenth", "two hundred five", 10/18/2026
+    EnUs,
+    /// "the eighteenth of October", "two hundred and five", 18/10/2026
+    EnGb,
+    /// "achtzehnter Oktober", 1.234,5 and 18.10.2026
+    De,
+}
+
+impl Locale {
+    pub const NAMES: [&str; 3] = ["en-us", "en-gb", "de"];
+
+    /// Parse a locale name from the config file
+    pub fn parse(name: &str) -> TtsResult<Self> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "en-us" | "en" => Ok(Locale::EnUs),
+            "en-gb" => Ok(Locale::EnGb),
+            "de" => Ok(Locale::De),
+            _ => Err(TtsError::ConfigError(format!(
+                "Unknown locale '{}' (expected {})",
+                name,
+                Locale::NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// The locale for a voice language, as an engine reports it: a code such
+    /// as "en-gb" or "de_DE", or a name such as "English (United States)"
+    pub fn from_language(language: &str) -> Option<Self> {
+        let language = language_code(language);
+        let (code, region) = language.split_once('-').unwrap_or((&language, ""));
+        match (code, region) {
+            ("en", "" | "us" | "ca" | "ph") => Some(Locale::EnUs),
+            ("en", _) => Some(Locale::EnGb),
+            ("de", _) => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::EnUs => "en-us",
+            Locale::EnGb => "en-gb",
+            Locale::De => "de",
+        }
+    }
+}
+
+/// Language names some engines report instead of codes, as SAPI does
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("english", "en"),
+    ("german", "de"),
+    ("deutsch", "de"),
+    ("french", "fr"),
+    ("spanish", "es"),
+    ("italian", "it"),
+    ("portuguese", "pt"),
+    ("dutch", "nl"),
+    ("polish", "pl"),
+    ("russian", "ru"),
+    ("japanese", "ja"),
+    ("chinese", "zh"),
+];
+
+/// Region names in parentheses after a language name
+const REGION_NAMES: &[(&str, &str)] = &[
+    ("united states", "us"),
+    ("united kingdom", "gb"),
+    ("canada", "ca"),
+    ("australia", "au"),
+    ("india", "in"),
+    ("ireland", "ie"),
+    ("philippines", "ph"),
+    ("germany", "de"),
+    ("austria", "at"),
+    ("switzerland", "ch"),
+    ("france", "fr"),
+    ("spain", "es"),
+    ("mexico", "mx"),
+    ("brazil", "br"),
+    ("portugal", "pt"),
+];
+
+/// A voice language as a lowercase code such as "en-gb", whether the engine
+/// reports a code such as "en_GB" or a name such as "English (United Kingdom)"
+///
+/// Names not in the table are returned lowercased.
+pub fn language_code(language: &str) -> String {
+    let language = language.trim().to_ascii_lowercase().replace('_', "-");
+    let (name, region) = match language.split_once('(') {
+        Some((name, region)) => (name.trim(), region.trim_end_matches(')').trim()),
+        None => (language.as_str(), ""),
+    };
+    let Some(&(_, code)) = LANGUAGE_NAMES.iter().find(|(known, _)| *known == name) else {
+        return language;
+    };
+    match REGION_NAMES.iter().find(|(known, _)| *known == region) {
+        Some((_, region)) => format!("{}-{}", code, region),
+        None => code.to_string(),
+    }
+}
+
+/// Filters run one after another on every text before it is spoken
+#[derive(Default)]
+pub struct Pipeline {
+    filters: Vec<Box<dyn TextFilter>>,
+}
+
+impl Pipeline {
+    /// An empty pipeline, which leaves text unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a pipeline from built-in filter names, such as [`DEFAULT_FILTERS`];
+    /// filters that need settings, such as the lexicon, are passed in ready
+    /// to use and picked by their name
+    pub fn from_names<S: AsRef<str>>(
+        names: &[S],
+        configured: Vec<Box<dyn TextFilter>>,
+    ) -> TtsResult<Self> {
+        let mut pipeline = Pipeline::new();
+        let mut configured: Vec<(String, Option<Box<dyn TextFilter>>)> = configured
+            .into_iter()
+            .map(|filter| (filter.name().to_string(), Some(filter)))
+            .collect();
+        for name in names {
+            let name = name.as_ref();
+            let slot = configured
+                .iter_mut()
+                .find(|(configured_name, _)| configured_name == name);
+            let filter: Box<dyn TextFilter> = match (name, slot) {
+                (_, Some((_, slot))) => slot.take().ok_or_else(|| {
+                    TtsError::ConfigError(format!("Filter '{}' is listed twice", name))
+                })?,
+                ("ansi", None) => Box::new(StripAnsi),
+                ("whitespace", None) => Box::new(NormalizeWhitespace),
+                ("urls", None) => Box::new(ShortenUrls),
+                (other, None) => {
+                    return Err(TtsError::ConfigError(format!(
+                        "Unknown text filter '{}' (expected {})",
+                        other,
+                        DEFAULT_FILTERS.join(", ")
+                    )));
+                }
+            };
+            pipeline.push(filter);
+        }
+        Ok(pipeline)
+    }
+
+    /// Add a filter at the end of the pipeline
+    pub fn push(&mut self, filter: Box<dyn TextFilter>) {
+        self.filters.push(filter);
+    }
+
+    /// Names of the filters, in the order they run
+    pub fn names(&self) -> Vec<&str> {
+        self.filters.iter().map(|filter| filter.name()).collect()
+    }
+
+    pub fn run(&self, text: &str) -> String {
+        self.filters
+            .iter()
+            .fold(text.to_string(), |text, filter| filter.apply(&text))
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Remove ANSI escape sequences, such as the colors in saved terminal output
+#[derive(Debug, Clone, Copy)]
+pub struct StripAnsi;
+
+impl TextFilter for StripAnsi {
+    fn name(&self) -> &str {
+        "ansi"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                // Control sequences end with a byte in @..~, as in ESC[31m
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // Operating system commands, such as window titles and links,
+                // end with BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Two-character escapes, such as ESC c
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
+/// Normalize line endings, remove control characters and collapse whitespace,
+/// as [`prepare_text`] does
+#[derive(Debug, Clone, Copy)]
+pub struct NormalizeWhitespace;
+
+impl TextFilter for NormalizeWhitespace {
+    fn name(&self) -> &str {
+        "whitespace"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        prepare_text(text)
+    }
+}
+
+/// Read web addresses as their site, e.g. "link to github.com"
+#[derive(Debug, Clone, Copy)]
+pub struct ShortenUrls;
+
+impl TextFilter for ShortenUrls {
+    fn name(&self) -> &str {
+        "urls"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = find_url(rest) {
+            result.push_str(&rest[..start]);
+            let raw = &rest[start..];
+            let length = raw
+                .find(|c: char| c.is_whitespace() || "<>\"".contains(c))
+                .unwrap_or(raw.len());
+            // Punctuation at the end belongs to the sentence, not the address
+            let mut url =
+                raw[..length].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\'']);
+            // Except the bracket closing an IPv6 host
+            if url.contains('[') && !url.contains(']') && raw[url.len()..].starts_with(']') {
+                url = &raw[..url.len() + 1];
+            }
+            let host = url
+                .split_once("://")
+                .map_or(url, |(_, rest)| rest)
+                .split(['/', '?', '#'])
+                .next()
+                .unwrap_or_default();
+            let host = host.rsplit('@').next().unwrap_or(host);
+            let host = match host.strip_prefix('[') {
+                Some(address) => address.split(']').next().unwrap_or(address),
+                None => host.split(':').next().unwrap_or(host),
+            };
+            result.push_str("link to ");
+            result.push_str(host.strip_prefix("www.").unwrap_or(host));
+            rest = &rest[start + url.len()..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Where the next web address starts, at the start of a word
+fn find_url(text: &str) -> Option<usize> {
+    ["https://", "http://", "www."]
+        .iter()
+        .filter_map(|prefix| {
+            text.match_indices(prefix)
+                .map(|(index, _)| index)
+                .find(|&index| {
+                    text[..index]
+                        .chars()
+                        .last()
+                        .is_none_or(|c| !c.is_alphanumeric() && c != '.' && c != '/')
+                        && text[index + prefix.len()..]
+                            .chars()
+                            .next()
+                            .is_some_and(|c| {
+                                c.is_alphanumeric() || (c == '[' && prefix.ends_with("//"))
+                            })
+                })
+        })
+        .min()
+}
@@ -1,5 +1,7 @@
 use crate::errors::{TtsError, TtsResult};
-use std::process::Command;
+use serde::Serialize;
+use std::path::Path;
+use std::process::{Command, Output};
 
 /// Configuration for text-to-speech synthesis
 #[derive(Debug, Clone)]
@@ -17,17 +19,97 @@ impl Default for SpeechConfig {
     }
 }
 
+impl SpeechConfig {
+    /// Check that the configured speech rate is within reasonable bounds
+    pub fn validate(&self) -> TtsResult<()> {
+        if let Some(rate) = self.rate {
+            validate_rate(rate)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate that a speech rate is within reasonable bounds
+pub fn validate_rate(rate: u32) -> TtsResult<()> {
+    // valid range is 50-1000 wpm
+    if !(50..=1000).contains(&rate) {
+        return Err(TtsError::ConfigError(format!(
+            "Speech rate {} is outside valid range (50-1000 words per minute)",
+            rate
+        )));
+    }
+    Ok(())
+}
+
+/// A voice installed on the system
+#[derive(Debug, Clone, Serialize)]
+pub struct Voice {
+    pub name: String,
+    pub language: String,
+    pub description: String,
+}
+
+/// Names of the engines, as returned by [`TextToSpeech::name`]
+pub const ENGINE_NAMES: [&str; 3] = ["say", "sapi", "espeak"];
+
+/// Inline markup an engine reads as a pronunciation instead of as words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhonemeMarkup {
+    /// espeak phoneme mnemonics in double brackets, e.g. `[[n'EnZIn]]`
+    Espeak,
+    /// SSML `<phoneme>` elements holding IPA
+    Ssml,
+}
+
+impl PhonemeMarkup {
+    /// Markup that makes the engine say `word` as `phonemes`
+    pub fn render(self, phonemes: &str, word: &str) -> String {
+        match self {
+            PhonemeMarkup::Espeak => format!("[[{}]]", phonemes),
+            PhonemeMarkup::Ssml => format!(
+                "{}{}\">{}</phoneme>",
+                SSML_PHONEME_START,
+                escape_xml(phonemes),
+                escape_xml(word)
+            ),
+        }
+    }
+}
+
+const SSML_PHONEME_START: &str = "<phoneme alphabet=\"ipa\" ph=\"";
+const SSML_PHONEME_END: &str = "</phoneme>";
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Trait for text-to-speech engines
 pub trait TextToSpeech {
+    /// Short name of the engine, one of [`ENGINE_NAMES`]
+    fn name(&self) -> &'static str;
+    /// Pronunciation markup the engine understands inside text, if any
+    fn phoneme_markup(&self) -> Option<PhonemeMarkup> {
+        None
+    }
+    /// Speak text through the system audio output
     fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()>;
+    /// Synthesize text into a WAV file instead of playing it
+    fn render_wav(&self, text: &str, config: &SpeechConfig, output: &Path) -> TtsResult<()>;
+    /// List the voices installed on this system, as reported by the engine
     fn list_voices(&self) -> TtsResult<String>;
+    /// List the voices installed on this system as structured entries
+    fn voices(&self) -> TtsResult<Vec<Voice>>;
 }
 
 /// macOS text-to-speech implementation using the 'say' command
 pub struct MacOsTts;
 
-impl TextToSpeech for MacOsTts {
-    fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()> {
+impl MacOsTts {
+    /// Build a `say` command with the voice and rate options applied
+    fn command(config: &SpeechConfig) -> Command {
         let mut cmd = Command::new("say");
 
         if let Some(voice) = &config.voice {
@@ -38,8 +120,11 @@ impl TextToSpeech for MacOsTts {
             cmd.args(["-r", &rate.to_string()]);
         }
 
-        let output = cmd.arg(text).output()?;
+        cmd
+    }
 
+    /// Turn a failed `say` run into the matching error
+    fn check_output(output: &Output, config: &SpeechConfig) -> TtsResult<()> {
         if !output.status.success() {
             let error_msg = String::from_utf8_lossy(&output.stderr);
             // Check if the error is due to an invalid voice
@@ -58,6 +143,28 @@ impl TextToSpeech for MacOsTts {
 
         Ok(())
     }
+}
+
+impl TextToSpeech for MacOsTts {
+    fn name(&self) -> &'static str {
+        "say"
+    }
+
+    fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()> {
+        let output = Self::command(config).arg(text).output()?;
+        Self::check_output(&output, config)
+    }
+
+    fn render_wav(&self, text: &str, config: &SpeechConfig, output: &Path) -> TtsResult<()> {
+        // `say` picks the container from the extension; force 16-bit PCM so the file is a plain WAV
+        let result = Self::command(config)
+            .arg("-o")
+            .arg(output)
+            .arg("--data-format=LEI16@22050")
+            .arg(text)
+            .output()?;
+        Self::check_output(&result, config)
+    }
 
     fn list_voices(&self) -> TtsResult<String> {
         let output = Command::new("say").arg("-v").arg("?").output()?;
@@ -70,16 +177,40 @@ impl TextToSpeech for MacOsTts {
             ))
         }
     }
+
+    fn voices(&self) -> TtsResult<Vec<Voice>> {
+        // Lines look like: "Good News           en_US    # Hello! My name is Good News."
+        let voices = self
+            .list_voices()?
+            .lines()
+            .filter_map(|line| {
+                let (left, sample) = line.split_once('#')?;
+                let (name, language) = left.trim().rsplit_once(char::is_whitespace)?;
+                Some(Voice {
+                    name: name.trim().to_string(),
+                    language: language.to_string(),
+                    description: sample.trim().to_string(),
+                })
+            })
+            .collect();
+        Ok(voices)
+    }
 }
 
 /// Windows text-to-speech implementation using PowerShell and SAPI
 pub struct WindowsTts;
 
-impl TextToSpeech for WindowsTts {
-    fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()> {
+impl WindowsTts {
+    /// Build the PowerShell script that drives SAPI, optionally writing to a WAV file
+    fn script(text: &str, config: &SpeechConfig, output: Option<&Path>) -> String {
         let mut ps_script = String::from("Add-Type -AssemblyName System.Speech; ");
         ps_script.push_str("$synth = New-Object System.Speech.Synthesis.SpeechSynthesizer; ");
 
+        if let Some(path) = output {
+            let escaped_path = path.display().to_string().replace("'", "''");
+            ps_script.push_str(&format!("$synth.SetOutputToWaveFile('{}'); ", escaped_path));
+        }
+
         if let Some(voice) = &config.voice {
             ps_script.push_str(&format!("$synth.SelectVoice('{}'); ", voice));
         }
@@ -90,12 +221,62 @@ impl TextToSpeech for WindowsTts {
             ps_script.push_str(&format!("$synth.Rate = {}; ", sapi_rate));
         }
 
-        // Escape single quotes in text
-        let escaped_text = text.replace("'", "''");
-        ps_script.push_str(&format!("$synth.Speak('{}');", escaped_text));
+        if text.contains(SSML_PHONEME_START) {
+            // Lexicon pronunciations are SSML phoneme elements; everything
+            // else is appended as plain text so it cannot be taken for markup
+            ps_script.push_str("$prompt = New-Object System.Speech.Synthesis.PromptBuilder; ");
+            for (is_markup, part) in Self::split_phonemes(text) {
+                let method = if is_markup {
+                    "AppendSsmlMarkup"
+                } else {
+                    "AppendText"
+                };
+                ps_script.push_str(&format!(
+                    "$prompt.{}('{}'); ",
+                    method,
+                    part.replace("'", "''")
+                ));
+            }
+            ps_script.push_str("$synth.Speak($prompt);");
+        } else {
+            // Escape single quotes in text
+            let escaped_text = text.replace("'", "''");
+            ps_script.push_str(&format!("$synth.Speak('{}');", escaped_text));
+        }
+
+        if output.is_some() {
+            // Release the file handle so the WAV is complete when PowerShell exits
+            ps_script.push_str(" $synth.Dispose();");
+        }
 
+        ps_script
+    }
+
+    /// Split text into plain runs and SSML phoneme elements, flagging the elements
+    fn split_phonemes(text: &str) -> Vec<(bool, &str)> {
+        let mut parts = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(SSML_PHONEME_START) {
+            let Some(length) = rest[start..].find(SSML_PHONEME_END) else {
+                break;
+            };
+            let end = start + length + SSML_PHONEME_END.len();
+            if start > 0 {
+                parts.push((false, &rest[..start]));
+            }
+            parts.push((true, &rest[start..end]));
+            rest = &rest[end..];
+        }
+        if !rest.is_empty() {
+            parts.push((false, rest));
+        }
+        parts
+    }
+
+    /// Run a SAPI script and turn a failure into the matching error
+    fn run(ps_script: &str, config: &SpeechConfig) -> TtsResult<()> {
         let output = Command::new("powershell")
-            .args(["-Command", &ps_script])
+            .args(["-Command", ps_script])
             .output()?;
 
         if !output.status.success() {
@@ -116,6 +297,24 @@ impl TextToSpeech for WindowsTts {
 
         Ok(())
     }
+}
+
+impl TextToSpeech for WindowsTts {
+    fn name(&self) -> &'static str {
+        "sapi"
+    }
+
+    fn phoneme_markup(&self) -> Option<PhonemeMarkup> {
+        Some(PhonemeMarkup::Ssml)
+    }
+
+    fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()> {
+        Self::run(&Self::script(text, config, None), config)
+    }
+
+    fn render_wav(&self, text: &str, config: &SpeechConfig, output: &Path) -> TtsResult<()> {
+        Self::run(&Self::script(text, config, Some(output)), config)
+    }
 
     fn list_voices(&self) -> TtsResult<String> {
         let ps_script = "Add-Type -AssemblyName System.Speech; \
@@ -136,13 +335,35 @@ impl TextToSpeech for WindowsTts {
             ))
         }
     }
+
+    fn voices(&self) -> TtsResult<Vec<Voice>> {
+        // Lines look like: "Microsoft Zira Desktop - Microsoft Zira Desktop - English (United States)"
+        let voices = self
+            .list_voices()?
+            .lines()
+            .filter_map(|line| {
+                let (name, description) = line.trim().split_once(" - ")?;
+                let language = description
+                    .rsplit_once(" - ")
+                    .map(|(_, language)| language)
+                    .unwrap_or_default();
+                Some(Voice {
+                    name: name.trim().to_string(),
+                    language: language.trim().to_string(),
+                    description: description.trim().to_string(),
+                })
+            })
+            .collect();
+        Ok(voices)
+    }
 }
 
 /// Linux text-to-speech implementation using espeak
 pub struct LinuxTts;
 
-impl TextToSpeech for LinuxTts {
-    fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()> {
+impl LinuxTts {
+    /// Build an `espeak` command with the voice and rate options applied
+    fn command(config: &SpeechConfig) -> Command {
         let mut cmd = Command::new("espeak");
 
         if let Some(voice) = &config.voice {
@@ -153,8 +374,11 @@ impl TextToSpeech for LinuxTts {
             cmd.args(["-s", &rate.to_string()]);
         }
 
-        let output = cmd.arg(text).output()?;
+        cmd
+    }
 
+    /// Turn a failed `espeak` run into the matching error
+    fn check_output(output: &Output, config: &SpeechConfig) -> TtsResult<()> {
         if !output.status.success() {
             let error_msg = String::from_utf8_lossy(&output.stderr);
             // Check if the error is due to an invalid voice
@@ -173,6 +397,30 @@ impl TextToSpeech for LinuxTts {
 
         Ok(())
     }
+}
+
+impl TextToSpeech for LinuxTts {
+    fn name(&self) -> &'static str {
+        "espeak"
+    }
+
+    fn phoneme_markup(&self) -> Option<PhonemeMarkup> {
+        Some(PhonemeMarkup::Espeak)
+    }
+
+    fn speak(&self, text: &str, config: &SpeechConfig) -> TtsResult<()> {
+        let output = Self::command(config).arg(text).output()?;
+        Self::check_output(&output, config)
+    }
+
+    fn render_wav(&self, text: &str, config: &SpeechConfig, output: &Path) -> TtsResult<()> {
+        let result = Self::command(config)
+            .arg("-w")
+            .arg(output)
+            .arg(text)
+            .output()?;
+        Self::check_output(&result, config)
+    }
 
     fn list_voices(&self) -> TtsResult<String> {
         let output = Command::new("espeak").arg("--voices").output()?;
@@ -185,6 +433,27 @@ impl TextToSpeech for LinuxTts {
             ))
         }
     }
+
+    fn voices(&self) -> TtsResult<Vec<Voice>> {
+        // Columns: Pty Language Age/Gender VoiceName File Other Languages (first line is the header)
+        let voices = self
+            .list_voices()?
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split_whitespace().collect();
+                if columns.len() < 5 {
+                    return None;
+                }
+                Some(Voice {
+                    name: columns[3].to_string(),
+                    language: columns[1].to_string(),
+                    description: columns[4].to_string(),
+                })
+            })
+            .collect();
+        Ok(voices)
+    }
 }
 
 /// Factory function to create the appropriate TTS engine for the current platform
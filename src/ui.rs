@@ -83,6 +83,61 @@ pub fn show_comprehensive_help() {
     println!("  cargo run -- --file welcome.txt");
     println!("  cargo run -- -f /path/to/your/document.txt");
     println!();
+    println!("  # Read a Markdown document, skipping its code blocks");
+    println!("  cargo run -- --file README.md --code-blocks skip");
+    println!();
+    println!("  # List the chapters of an EPUB book, then read one");
+    println!("  cargo run -- --file book.epub --list-chapters");
+    println!("  cargo run -- --file book.epub --chapter 3");
+    println!();
+    println!("  # Read pages 3 to 10 of a PDF");
+    println!("  cargo run -- --file paper.pdf --pages 3-10");
+    println!();
+    println!("  # Read two columns of a spreadsheet export, rows 20 to 40");
+    println!("  cargo run -- --file staff.csv --columns Name,Role --rows 20-40");
+    println!();
+    println!("  # Hear one section of a config file as an outline");
+    println!("  cargo run -- --file deploy.yaml --select services.web");
+    println!();
+    println!("  # Read Org-mode notes without TODO keywords and tags");
+    println!("  cargo run -- --file notes.org --skip-todo");
+    println!();
+    println!("  # Use a config file for the text filters and the lexicon");
+    println!("  cargo run -- --file build.log --config ~/t2v.toml");
+    println!();
+    println!("  # Say product names right with a pronunciation lexicon");
+    println!("  cargo run -- --file demo-script.md --lexicon product-names.toml");
+    println!();
+    println!("  # Morning briefing: only the feed items not heard yet");
+    println!("  cargo run -- --file feeds/team.rss --heard ~/.t2v-heard");
+    println!();
+    println!("  # Review source code by ear, with identifiers split into words");
+    println!("  cargo run -- --file src/main.rs --code full");
+    println!();
+    println!("  # Proofread a LaTeX paper by ear, with math read aloud");
+    println!("  cargo run -- --file paper.tex");
+    println!();
+    println!("  # Read an exported mailbox, skipping quoted replies");
+    println!("  cargo run -- --file support.mbox");
+    println!();
+    println!("  # Turn subtitles into a timed voice-over track");
+    println!("  cargo run -- --file training.srt --output voice-over.wav");
+    println!();
+    println!("  # Read a scanned handout in German with OCR");
+    println!("  cargo run -- --file handout.png --ocr-lang deu");
+    println!();
+    println!("  # Read several files, a directory or a glob pattern");
+    println!("  cargo run -- --file intro.txt chapter1.txt");
+    println!("  cargo run -- --file notes/ --sort mtime --announce");
+    println!("  cargo run -- --file \"notes/*.txt\"");
+    println!();
+    println!("  # Keep speaking new lines appended to a log file (Ctrl+C to stop)");
+    println!("  cargo run -- --file deploy.log --follow");
+    println!();
+    println!("  # Read piped text from standard input");
+    println!("  echo \"Build finished\" | cargo run");
+    println!("  tail -f build.log | cargo run -- --incremental");
+    println!();
 
     println!("{}", "🎭 VOICE SELECTION EXAMPLES:".bright_yellow().bold());
     println!("  # Use a specific voice");